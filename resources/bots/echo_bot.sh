@@ -0,0 +1,8 @@
+#!/bin/sh
+# reference bot for the external bot protocol, see src/bot_protocol.rs.
+# answers every state line with the tick it was sent and an idle input.
+#
+#   rasteroids --controller "external:sh resources/bots/echo_bot.sh"
+while read -r _ tick _; do
+    echo "$tick 0 0 0"
+done
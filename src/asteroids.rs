@@ -18,11 +18,23 @@ use crate::collision;
 
 use crate::circles;
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::fmt;
+use std::str::FromStr;
 
 const SMALL_ASTEROID_INDEX: usize = 0;
 const BIG_ASTEROID_INDEX: usize = 1;
 
+/// one life, so the first hit still ends the game unless more are given.
+const STARTING_LIVES: u32 = 1;
+/// ticks the player can't be hit for after respawning.
+const RESPAWN_INVULNERABLE_TICKS: u32 = 30;
+/// the world is drawn to a texture this many pixels square, then stretched
+/// over the window.
+pub const RENDER_SIZE: u32 = 150;
+
 
 /// contains a list of resources used for rendering. 
 pub struct ImageResources<'a> {
@@ -62,6 +74,16 @@ pub struct MoveAblePos {
     direction: f64,
 }
 
+impl MoveAblePos {
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    pub fn direction(&self) -> f64 {
+        self.direction
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Asteroid {
     rust_sux: MoveAblePos,
@@ -69,6 +91,22 @@ pub struct Asteroid {
 }
 
 impl Asteroid {
+    pub fn new(pos_x: f64, pos_y: f64, velocity: f64, direction: f64, radius: f64) -> Self {
+        Self {
+            rust_sux: MoveAblePos {
+                pos_x,
+                pos_y,
+                velocity,
+                direction,
+            },
+            radius,
+        }
+    }
+
+    pub fn movement(&self) -> &MoveAblePos {
+        &self.rust_sux
+    }
+
     pub fn bounding_box(&self) -> collision::Circle {
         return collision::Circle {
             pos_x: self.rust_sux.pos_x,
@@ -86,6 +124,11 @@ pub struct Player {
 }
 
 impl Player {
+    /// heading of the ship in radians, 0 -> 2PI.
+    pub fn direction(&self) -> f64 {
+        self.rust_sux.direction
+    }
+
     pub fn bounding_box(&self) -> collision::Circle {
         return collision::Circle {
             pos_x: self.rust_sux.pos_x,
@@ -96,7 +139,7 @@ impl Player {
 }
 
 #[derive(Clone, Debug)]
-struct Bullet {
+pub struct Bullet {
     rust_sux: MoveAblePos,
     /// amount of update time the bullet will exists for.
     life_time: f64,
@@ -104,7 +147,11 @@ struct Bullet {
 }
 
 impl Bullet {
-    fn bounding_box(&self) -> collision::Circle {
+    pub fn movement(&self) -> &MoveAblePos {
+        &self.rust_sux
+    }
+
+    pub fn bounding_box(&self) -> collision::Circle {
         return collision::Circle {
             pos_x: self.rust_sux.pos_x,
             pos_y: self.rust_sux.pos_y,
@@ -113,20 +160,214 @@ impl Bullet {
     }
 }
 
+/// gameplay numbers that can be changed while the game is running.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tuning {
+    /// amount of update time a bullet exists for.
+    pub bullet_life_time: f64,
+    /// ticks between shots.
+    pub fire_cooldown: i16,
+    /// speed of the ship while the thrusters are on.
+    pub thrust: f64,
+    /// fraction of the ship's speed lost per unit of time with the thrusters off.
+    pub drag: f64,
+    pub world_width: f64,
+    pub world_height: f64,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            bullet_life_time: 20.0,
+            fire_cooldown: 20,
+            thrust: 2.0,
+            drag: 10.0,
+            world_width: 100.0,
+            world_height: 100.0,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GameState {
     pub asteroids: Vec<Asteroid>,
     pub player: Player,
     bullets: Vec<Bullet>,
+    /// scratch space for asteroids broken up during an update.
+    split_asteroids: Vec<Asteroid>,
     shoot_bullet_cd: i16,
-    world_width: f64,
-    world_height: f64,
+    pub tuning: Tuning,
     // if true then the game is finished.
     pub game_over: bool,
     pub game_over_is_win: bool,
     pub score: u64,
+    /// tries left, game is over once the last one is used up.
+    pub lives: u32,
+    /// current wave, starts at 1 and only changes when a wave is started
+    /// by hand, e.g. from the console.
+    pub wave: u32,
+    /// while above zero the player can't be hit, counts down each tick.
+    pub invulnerable_ticks: u32,
+    /// if true asteroids never hurt the player.
+    pub god_mode: bool,
+    /// seed the asteroid field was generated from.
+    pub seed: u64,
+    /// number of updates the game has run for.
+    pub ticks: u64,
+    pub shots_fired: u64,
+    /// bullets that destroyed an asteroid.
+    pub shots_hit: u64,
 }
 
+impl GameState {
+    pub fn bullets(&self) -> &[Bullet] {
+        &self.bullets
+    }
+
+    /// width and height of the wrap around world.
+    pub fn world_size(&self) -> (f64, f64) {
+        (self.tuning.world_width, self.tuning.world_height)
+    }
+
+    /// every entity in the world, the player first.
+    pub fn entity_ids(&self) -> Vec<EntityId> {
+        let asteroids = (0..self.asteroids.len()).map(EntityId::Asteroid);
+        let bullets = (0..self.bullets.len()).map(EntityId::Bullet);
+        std::iter::once(EntityId::Player)
+            .chain(asteroids)
+            .chain(bullets)
+            .collect()
+    }
+
+    /// movement, radius and lifetime of an entity, if it still exists.
+    fn entity_parts(&self, id: EntityId) -> Option<(&MoveAblePos, f64, Option<f64>)> {
+        match id {
+            EntityId::Player => Some((&self.player.rust_sux, self.player.radius, None)),
+            EntityId::Asteroid(i) => self.asteroids.get(i).map(|a| (&a.rust_sux, a.radius, None)),
+            EntityId::Bullet(i) => self
+                .bullets
+                .get(i)
+                .map(|b| (&b.rust_sux, b.radius, Some(b.life_time))),
+        }
+    }
+
+    fn entity_parts_mut(
+        &mut self,
+        id: EntityId,
+    ) -> Option<(&mut MoveAblePos, &mut f64, Option<&mut f64>)> {
+        match id {
+            EntityId::Player => Some((&mut self.player.rust_sux, &mut self.player.radius, None)),
+            EntityId::Asteroid(i) => self
+                .asteroids
+                .get_mut(i)
+                .map(|a| (&mut a.rust_sux, &mut a.radius, None)),
+            EntityId::Bullet(i) => self
+                .bullets
+                .get_mut(i)
+                .map(|b| (&mut b.rust_sux, &mut b.radius, Some(&mut b.life_time))),
+        }
+    }
+
+    /// value of one field of an entity, None if the entity is gone or
+    /// doesn't have that field, only bullets have a lifetime.
+    pub fn entity_field(&self, id: EntityId, field: EntityField) -> Option<f64> {
+        let (movement, radius, life_time) = self.entity_parts(id)?;
+        match field {
+            EntityField::PosX => Some(movement.pos_x),
+            EntityField::PosY => Some(movement.pos_y),
+            EntityField::Velocity => Some(movement.velocity),
+            EntityField::Direction => Some(movement.direction),
+            EntityField::Radius => Some(radius),
+            EntityField::LifeTime => life_time,
+        }
+    }
+
+    /// changes one field of an entity, e.g. from the inspector.
+    pub fn set_entity_field(
+        &mut self,
+        id: EntityId,
+        field: EntityField,
+        value: f64,
+    ) -> Result<(), String> {
+        if !value.is_finite() {
+            return Err(format!("{} must be a number", field.name()));
+        }
+        let (movement, radius, life_time) = self
+            .entity_parts_mut(id)
+            .ok_or_else(|| format!("{} no longer exists", id))?;
+        match field {
+            EntityField::PosX => movement.pos_x = value,
+            EntityField::PosY => movement.pos_y = value,
+            EntityField::Velocity => movement.velocity = value,
+            // kept to 0 -> 2PI like everywhere else.
+            EntityField::Direction => movement.direction = value.rem_euclid(2.0 * std::f64::consts::PI),
+            EntityField::Radius if value <= 0.0 => {
+                return Err(String::from("radius must be above 0"));
+            }
+            EntityField::Radius => *radius = value,
+            EntityField::LifeTime => match life_time {
+                Some(_) if value < 0.0 => return Err(String::from("lifetime can't be negative")),
+                Some(life_time) => *life_time = value,
+                None => return Err(format!("{} has no lifetime", id)),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// an entity in the world by kind and index, indexes shift as things are
+/// destroyed so these only stay good for a frame or so.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityId {
+    Player,
+    Asteroid(usize),
+    Bullet(usize),
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntityId::Player => write!(f, "player"),
+            EntityId::Asteroid(i) => write!(f, "asteroid {}", i),
+            EntityId::Bullet(i) => write!(f, "bullet {}", i),
+        }
+    }
+}
+
+/// numbers of an entity that can be looked at and changed while playing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityField {
+    PosX,
+    PosY,
+    Velocity,
+    Direction,
+    Radius,
+    LifeTime,
+}
+
+impl EntityField {
+    pub const ALL: [EntityField; 6] = [
+        EntityField::PosX,
+        EntityField::PosY,
+        EntityField::Velocity,
+        EntityField::Direction,
+        EntityField::Radius,
+        EntityField::LifeTime,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntityField::PosX => "x",
+            EntityField::PosY => "y",
+            EntityField::Velocity => "velocity",
+            EntityField::Direction => "direction",
+            EntityField::Radius => "radius",
+            EntityField::LifeTime => "lifetime",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct GameInput {
     // radians value for what to update the ship with.
     pub rotation: f64,
@@ -138,7 +379,47 @@ pub struct GameInput {
     pub thrusters: bool,
 }
 
+/// single line form used by replays, "<rotation> <shoot> <thrusters>"
+/// where shoot and thrusters are 0 or 1.
+impl fmt::Display for GameInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.rotation, self.shoot as u8, self.thrusters as u8
+        )
+    }
+}
+
+impl FromStr for GameInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != 3 {
+            return Err(format!("expected 3 fields got {}: '{}'", parts.len(), s));
+        }
+        let flag = |v: &str| match v {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(format!("expected 0 or 1 got '{}'", v)),
+        };
+        Ok(GameInput {
+            rotation: parts[0]
+                .parse()
+                .map_err(|_| format!("invalid rotation '{}'", parts[0]))?,
+            shoot: flag(parts[1])?,
+            thrusters: flag(parts[2])?,
+        })
+    }
+}
+
 pub fn game_init() -> GameState {
+    game_init_seeded(rand::thread_rng().gen())
+}
+
+/// same seed always produces the same starting asteroid field.
+pub fn game_init_seeded(seed: u64) -> GameState {
     let mut game_state = GameState {
         asteroids: vec![],
         game_over: false,
@@ -153,26 +434,57 @@ pub fn game_init() -> GameState {
             radius: 10.0,
         },
         bullets: vec![],
-        world_width: 100.0,
-        world_height: 100.0,
+        split_asteroids: vec![],
+        tuning: Tuning::default(),
         shoot_bullet_cd: 0,
         score: 0,
+        seed,
+        ticks: 0,
+        shots_fired: 0,
+        shots_hit: 0,
+        lives: STARTING_LIVES,
+        wave: 1,
+        invulnerable_ticks: 0,
+        god_mode: false,
     };
 
-    let mut rng = rand::thread_rng();
+    start_wave(&mut game_state, 1);
+    return game_state;
+}
+
+/// replaces the asteroid field with the one for the given wave.
+/// each wave of a seeded game always has the same layout.
+pub fn start_wave(game_state: &mut GameState, wave: u32) {
+    let wave = wave.max(1);
+    game_state.wave = wave;
+    game_state.asteroids.clear();
 
-    for _i in 0..rng.gen_range(5, 10) {
+    let mut rng = StdRng::seed_from_u64(game_state.seed.wrapping_add(wave as u64 - 1));
+
+    // two more asteroids every wave.
+    let count = rng.gen_range(5u32, 10).saturating_add((wave - 1).saturating_mul(2));
+    for _i in 0..count {
         game_state.asteroids.push(Asteroid {
             rust_sux: MoveAblePos {
-                pos_x: rng.gen_range(10.0, 50.0),
-                pos_y: rng.gen_range(10.0, 50.0),
+                pos_x: rng.gen_range(0.1, 0.5) * game_state.tuning.world_width,
+                pos_y: rng.gen_range(0.1, 0.5) * game_state.tuning.world_height,
                 velocity: rng.gen_range(1.0, 2.0),
                 direction: rng.gen_range(0.0, std::f64::consts::PI),
             },
             radius: 8.0,
         });
     }
-    return game_state;
+}
+
+// puts the player back in the middle of the world after losing a life.
+fn respawn_player(game_state: &mut GameState) {
+    game_state.player.rust_sux = MoveAblePos {
+        pos_x: game_state.tuning.world_width / 2.0,
+        pos_y: game_state.tuning.world_height / 2.0,
+        velocity: 0.0,
+        direction: 0.0,
+    };
+    game_state.invulnerable_ticks = RESPAWN_INVULNERABLE_TICKS;
 }
 
 fn update_pos(r: &mut MoveAblePos, dt: f64, world_width: f64, world_height: f64) {
@@ -204,84 +516,96 @@ fn shoot_bullet(game_state: &mut GameState) -> () {
             velocity: p.rust_sux.velocity + 2.0,
             direction: p.rust_sux.direction,
         },
-        life_time: 20.0,
+        life_time: game_state.tuning.bullet_life_time,
         radius: 2.0,
     };
 
     game_state.bullets.push(bullet);
 }
 
-// update game logic
-fn game_state_update(game_state: GameState, dt: f64, game_input: &GameInput) -> GameState {
-    let mut new_state = game_state.clone();
-
-    new_state.shoot_bullet_cd = game_state.shoot_bullet_cd - 1;
+// update game logic, done in place so a steady state tick doesn't allocate.
+fn game_state_update(game_state: &mut GameState, dt: f64, game_input: &GameInput) {
+    game_state.ticks += 1;
+    game_state.shoot_bullet_cd -= 1;
 
-    if new_state.shoot_bullet_cd < 0 {
-        new_state.shoot_bullet_cd = 0;
+    if game_state.shoot_bullet_cd < 0 {
+        game_state.shoot_bullet_cd = 0;
     }
-    if game_input.shoot && new_state.shoot_bullet_cd == 0 {
-        shoot_bullet(&mut new_state);
-        // todo: what should the cd be?
-        new_state.shoot_bullet_cd = 20;
+    if game_input.shoot && game_state.shoot_bullet_cd == 0 {
+        shoot_bullet(game_state);
+        game_state.shots_fired += 1;
+        game_state.shoot_bullet_cd = game_state.tuning.fire_cooldown;
     }
 
     if game_input.thrusters {
-        new_state.player.rust_sux.velocity = 2.0;
+        game_state.player.rust_sux.velocity = game_state.tuning.thrust;
     } else {
-        // need some sort of decay
-        new_state.player.rust_sux.velocity = 0.0;
+        // drag is the fraction of speed lost per unit of time.
+        let decay = (1.0 - game_state.tuning.drag * dt).max(0.0);
+        game_state.player.rust_sux.velocity *= decay;
     }
 
     // todo: add in wrap around for bullets and asteroids and player etc.
-    new_state.player.rust_sux.direction += 0.5 * game_input.rotation * dt;
+    game_state.player.rust_sux.direction += 0.5 * game_input.rotation * dt;
 
-    if new_state.player.rust_sux.direction > 2.0 * std::f64::consts::PI {
-        new_state.player.rust_sux.direction -= 2.0 * std::f64::consts::PI;
+    if game_state.player.rust_sux.direction > 2.0 * std::f64::consts::PI {
+        game_state.player.rust_sux.direction -= 2.0 * std::f64::consts::PI;
     }
 
-    if new_state.player.rust_sux.direction < 0.0 {
-        new_state.player.rust_sux.direction += 2.0 * std::f64::consts::PI;
+    if game_state.player.rust_sux.direction < 0.0 {
+        game_state.player.rust_sux.direction += 2.0 * std::f64::consts::PI;
     }
 
-    let player = &mut new_state.player;
+    let world_width = game_state.tuning.world_width;
+    let world_height = game_state.tuning.world_height;
 
     update_pos(
-        &mut player.rust_sux,
+        &mut game_state.player.rust_sux,
         dt,
-        game_state.world_width,
-        game_state.world_height,
+        world_width,
+        world_height,
     );
 
-    for ast in new_state.asteroids.iter_mut() {
-        update_pos(
-            &mut ast.rust_sux,
-            dt,
-            game_state.world_width,
-            game_state.world_height,
-        );
+    for ast in game_state.asteroids.iter_mut() {
+        update_pos(&mut ast.rust_sux, dt, world_width, world_height);
     }
 
-    for bullet in new_state.bullets.iter_mut() {
-        update_pos(
-            &mut bullet.rust_sux,
-            dt,
-            game_state.world_width,
-            game_state.world_height,
-        );
+    for bullet in game_state.bullets.iter_mut() {
+        update_pos(&mut bullet.rust_sux, dt, world_width, world_height);
         bullet.life_time -= 1.0 * dt;
     }
 
-    new_state.bullets.retain(|bull| bull.life_time > 0.0);
+    game_state.bullets.retain(|bull| bull.life_time > 0.0);
 
-    // check for collision
-    let mut new_asteroids = Vec::new();
+    // update for player asteroid collision.
+    let player_box = game_state.player.bounding_box();
+    if game_state.invulnerable_ticks > 0 {
+        game_state.invulnerable_ticks -= 1;
+    } else if !game_state.god_mode
+        && game_state
+            .asteroids
+            .iter()
+            .any(|ast| collision::collides(&ast.bounding_box(), &player_box))
+    {
+        game_state.lives = game_state.lives.saturating_sub(1);
+        if game_state.lives == 0 {
+            game_state.game_over = true;
+        } else {
+            respawn_player(game_state);
+        }
+    }
 
     // update for asteroids and bullets.
-    for ast in new_state.asteroids.iter() {
-        let mut deleted_aster = false;
-        // todo: switch to filter on lifetime and can move retain to after this double loop?
-        for bull in new_state.bullets.iter_mut() {
+    // pieces of broken asteroids are collected in split_asteroids, which
+    // keeps its capacity between ticks, then moved over after the retain.
+    let bullets = &mut game_state.bullets;
+    let split_asteroids = &mut game_state.split_asteroids;
+    let score = &mut game_state.score;
+    let shots_hit = &mut game_state.shots_hit;
+    game_state.asteroids.retain(|ast| {
+        // wouldn't want a bullet to be able to kill two asteroids, so spent
+        // bullets are skipped until they're cleared out below.
+        for bull in bullets.iter_mut().filter(|b| b.life_time > 0.0) {
             if collision::collides(&ast.bounding_box(), &bull.bounding_box()) {
                 // break the asteroid into two, and give some random direction and velocity.
                 // remove the bullet.
@@ -290,7 +614,7 @@ fn game_state_update(game_state: GameState, dt: f64, game_input: &GameInput) ->
                 // large asteroid
                 if ast.radius > 3.0 {
                     // add two asteroids.
-                    new_asteroids.push(Asteroid {
+                    split_asteroids.push(Asteroid {
                         rust_sux: MoveAblePos {
                             pos_x: ast.rust_sux.pos_x,
                             pos_y: ast.rust_sux.pos_y,
@@ -301,7 +625,7 @@ fn game_state_update(game_state: GameState, dt: f64, game_input: &GameInput) ->
                         radius: ast.radius / 2.0,
                     });
 
-                    new_asteroids.push(Asteroid {
+                    split_asteroids.push(Asteroid {
                         rust_sux: MoveAblePos {
                             pos_x: ast.rust_sux.pos_x,
                             pos_y: ast.rust_sux.pos_y,
@@ -313,47 +637,34 @@ fn game_state_update(game_state: GameState, dt: f64, game_input: &GameInput) ->
                         radius: 3.0,
                     });
                 }
-                deleted_aster = true;
                 // 100 points per asteroid killed.
-                new_state.score += 100;
+                *score += 100;
+                *shots_hit += 1;
                 bull.life_time = 0.0;
-                break;
+                return false;
             }
         }
-        // is this good here? wouldn't want a bullet to
-        // be able to kill two asteroids right?
-        new_state.bullets.retain(|bull| bull.life_time > 0.0);
-
-        if !deleted_aster {
-            new_asteroids.push(ast.clone());
-        }
-    }
-
-    // update for player asteroid collision.
-    for ast in new_state.asteroids.iter() {
-        if collision::collides(&ast.bounding_box(), &new_state.player.bounding_box()) {
-            new_state.game_over = true;
-            break;
-        }
+        true
+    });
+    game_state.bullets.retain(|bull| bull.life_time > 0.0);
+    game_state
+        .asteroids
+        .append(&mut game_state.split_asteroids);
+
+    if game_state.asteroids.is_empty() {
+        game_state.game_over = true;
+        game_state.game_over_is_win = true;
     }
-
-    new_state.asteroids = new_asteroids;
-
-    if new_state.asteroids.len() == 0 {
-        new_state.game_over = true;
-        new_state.game_over_is_win = true;
-    }
-    return new_state;
 }
 
 #[cfg(feature = "gui")]
 pub fn game_sdl2_render(game_state: &GameState, canvas: &mut Canvas<Window>,
-                        image_resources: &ImageResources) -> () {
-    canvas.set_draw_color(Color::RGB(0, 0, 0));
+                        image_resources: &ImageResources, background: Color) -> () {
+    canvas.set_draw_color(background);
     // put this into a asteroids specific draw function.
 
     let texture_creator = canvas.texture_creator();
-    let mut new_texture = texture_creator.create_texture_target(texture_creator.default_pixel_format(), 150, 150).unwrap();
+    let mut new_texture = texture_creator.create_texture_target(texture_creator.default_pixel_format(), RENDER_SIZE, RENDER_SIZE).unwrap();
 
     canvas.with_texture_canvas(&mut new_texture, |texture_canvas| {
         texture_canvas.clear();
@@ -393,8 +704,17 @@ pub fn game_sdl2_render(game_state: &GameState, canvas: &mut Canvas<Window>,
 }    
 
 
-pub fn game_update(game_state: GameState, dt: f64, game_input: &GameInput) -> GameState {
-    game_state_update(game_state, dt, &game_input)
+/// advances the game one tick in place.
+pub fn game_update(game_state: &mut GameState, dt: f64, game_input: &GameInput) {
+    game_state_update(game_state, dt, game_input);
+}
+
+/// old by value api, clones the whole world every tick.
+/// only kept around to benchmark against game_update.
+pub fn game_update_cloned(game_state: GameState, dt: f64, game_input: &GameInput) -> GameState {
+    let mut new_state = game_state.clone();
+    game_state_update(&mut new_state, dt, game_input);
+    new_state
 }
 
 #[cfg(all(test, not(feature = "gui")))]
@@ -453,7 +773,8 @@ mod tests {
             thrusters: false,
         };
 
-        let mut new_state = game_update(&game_state, 0.1, &game_input);
+        let mut new_state = game_state;
+        game_update(&mut new_state, 0.1, &game_input);
         assert_eq!(new_state.bullets.len(), 0);
 
         // currently can't assert that bullet shoots cause it could collide with an asteroid
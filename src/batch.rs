@@ -0,0 +1,266 @@
+/// runs large numbers of independent headless games spread across all cores.
+///
+/// rasteroids batch [--games N] [--seed S] [--max-ticks T] [--threads T] <controller>
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::controller::{self, Controller};
+use crate::simulation::{self, GameStats};
+use crate::utils::parse_arg_value;
+
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    pub games: u64,
+    /// game i is played with seed base_seed + i.
+    pub base_seed: u64,
+    /// games still running after this many ticks are cut off.
+    pub max_ticks: u64,
+    /// worker threads, 0 uses one per available core.
+    pub threads: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            games: 1000,
+            base_seed: 0,
+            max_ticks: 10_000,
+            threads: 0,
+        }
+    }
+}
+
+/// aggregate results of every game in a batch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchStats {
+    pub games: u64,
+    /// games that hit max_ticks before game over.
+    pub timed_out: u64,
+    pub total_score: u64,
+    pub min_score: u64,
+    pub max_score: u64,
+    /// sum of score squared, kept for the standard deviation.
+    score_squares: f64,
+    pub total_ticks: u64,
+    pub shots_fired: u64,
+    pub shots_hit: u64,
+    /// wall clock time the whole batch took.
+    pub elapsed: Duration,
+}
+
+impl BatchStats {
+    fn add(&mut self, stats: &GameStats) {
+        if self.games == 0 {
+            self.min_score = stats.score;
+        }
+        self.games += 1;
+        self.timed_out += stats.timed_out as u64;
+        self.total_score += stats.score;
+        self.min_score = self.min_score.min(stats.score);
+        self.max_score = self.max_score.max(stats.score);
+        self.score_squares += (stats.score as f64).powi(2);
+        self.total_ticks += stats.ticks;
+        self.shots_fired += stats.shots_fired;
+        self.shots_hit += stats.shots_hit;
+    }
+
+    fn merge(&mut self, other: &BatchStats) {
+        if other.games == 0 {
+            return;
+        }
+        if self.games == 0 {
+            self.min_score = other.min_score;
+        }
+        self.games += other.games;
+        self.timed_out += other.timed_out;
+        self.total_score += other.total_score;
+        self.min_score = self.min_score.min(other.min_score);
+        self.max_score = self.max_score.max(other.max_score);
+        self.score_squares += other.score_squares;
+        self.total_ticks += other.total_ticks;
+        self.shots_fired += other.shots_fired;
+        self.shots_hit += other.shots_hit;
+    }
+
+    pub fn mean_score(&self) -> f64 {
+        simulation::mean(self.total_score, self.games)
+    }
+
+    pub fn score_std_dev(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        let mean = self.mean_score();
+        (self.score_squares / self.games as f64 - mean * mean)
+            .max(0.0)
+            .sqrt()
+    }
+
+    pub fn mean_ticks(&self) -> f64 {
+        simulation::mean(self.total_ticks, self.games)
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        simulation::accuracy(self.shots_hit, self.shots_fired)
+    }
+
+    pub fn games_per_second(&self) -> f64 {
+        self.games as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// plays config.games games in parallel, make_controller is called once per
+/// game with that game's seed and on the worker thread that plays it.
+pub fn run_batch<F>(config: &BatchConfig, make_controller: F) -> BatchStats
+where
+    F: Fn(u64) -> Box<dyn Controller> + Sync,
+{
+    let start = Instant::now();
+    let threads = if config.threads == 0 {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    } else {
+        config.threads
+    };
+    let threads = threads.min(config.games.max(1) as usize);
+
+    // workers pull game numbers off a shared counter so one slow game
+    // doesn't hold up a whole pre assigned chunk.
+    let next_game = AtomicU64::new(0);
+    let mut stats = BatchStats::default();
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut local = BatchStats::default();
+                    loop {
+                        let game = next_game.fetch_add(1, Ordering::Relaxed);
+                        if game >= config.games {
+                            break;
+                        }
+                        let seed = config.base_seed.wrapping_add(game);
+                        let mut player = make_controller(seed);
+                        local.add(&simulation::play_game(
+                            player.as_mut(),
+                            seed,
+                            config.max_ticks,
+                        ));
+                    }
+                    local
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            stats.merge(&worker.join().expect("batch worker panicked"));
+        }
+    });
+
+    stats.elapsed = start.elapsed();
+    stats
+}
+
+/// entry point for the batch command, args are everything after "batch".
+pub fn run_from_args(args: &[String]) -> Result<(), String> {
+    let mut config = BatchConfig::default();
+    let mut spec = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--games" => config.games = parse_arg_value(arg, iter.next())?,
+            "--seed" => config.base_seed = parse_arg_value(arg, iter.next())?,
+            "--max-ticks" => config.max_ticks = parse_arg_value(arg, iter.next())?,
+            "--threads" => config.threads = parse_arg_value(arg, iter.next())?,
+            _ if arg.starts_with("--") => return Err(format!("unknown argument: {}", arg)),
+            _ if spec.is_none() => spec = Some(arg.clone()),
+            _ => return Err(String::from("batch only takes a single controller")),
+        }
+    }
+
+    let spec = spec.ok_or_else(|| {
+        String::from(
+            "usage: rasteroids batch [--games N] [--seed S] [--max-ticks T] [--threads T] <controller>",
+        )
+    })?;
+    if spec == "keyboard" {
+        return Err(String::from("keyboard can't play headless games"));
+    }
+    // fail on a bad spec up front rather than in every worker.
+    controller::from_spec(&spec)?;
+
+    let stats = run_batch(&config, |_| {
+        controller::from_spec(&spec).expect("controller spec already checked")
+    });
+    println!(
+        "games: {} ({} cut off at {} ticks)",
+        stats.games, stats.timed_out, config.max_ticks
+    );
+    println!(
+        "score: mean {:.1} std dev {:.1} min {} max {}",
+        stats.mean_score(),
+        stats.score_std_dev(),
+        stats.min_score,
+        stats.max_score
+    );
+    println!("survival: mean {:.1} ticks", stats.mean_ticks());
+    println!("accuracy: {:.1}%", stats.accuracy() * 100.0);
+    println!(
+        "took {:.2}s, {:.1} games/s",
+        stats.elapsed.as_secs_f64(),
+        stats.games_per_second()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::ScriptedController;
+
+    fn config(games: u64, threads: usize) -> BatchConfig {
+        BatchConfig {
+            games,
+            base_seed: 11,
+            max_ticks: 300,
+            threads,
+        }
+    }
+
+    #[test]
+    fn test_batch_matches_serial_games() {
+        let stats = run_batch(&config(20, 4), |_| Box::new(ScriptedController::new()));
+        assert_eq!(stats.games, 20);
+
+        let mut serial = BatchStats::default();
+        for seed in 11..31 {
+            serial.add(&simulation::play_game(
+                &mut ScriptedController::new(),
+                seed,
+                300,
+            ));
+        }
+        serial.elapsed = stats.elapsed;
+        assert_eq!(stats, serial);
+    }
+
+    #[test]
+    fn test_thread_count_doesnt_change_results() {
+        let mut single = run_batch(&config(8, 1), |_| Box::new(ScriptedController::new()));
+        let mut many = run_batch(&config(8, 0), |_| Box::new(ScriptedController::new()));
+        single.elapsed = Duration::default();
+        many.elapsed = Duration::default();
+        assert_eq!(single, many);
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        let stats = run_batch(&config(0, 0), |_| Box::new(ScriptedController::new()));
+        assert_eq!(stats.games, 0);
+        assert_eq!(stats.mean_score(), 0.0);
+        assert_eq!(stats.score_std_dev(), 0.0);
+    }
+}
@@ -0,0 +1,192 @@
+/// console commands bound to keys, run when the key is pressed while playing.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// bindings are saved here as a script of bind commands and exec'd at startup.
+pub const BINDS_FILE: &str = "binds.cfg";
+
+pub struct Bindings {
+    /// lowercase key name to the command line it runs.
+    binds: BTreeMap<String, String>,
+    /// the bindings written to BINDS_FILE, binds without the changes made
+    /// while not persistent.
+    saved: BTreeMap<String, String>,
+    /// while false changes only last until the game closes, e.g. ones made
+    /// by autoexec.
+    persistent: bool,
+    /// set when the saved bindings change, so they're only written out when
+    /// needed.
+    changed: bool,
+}
+
+/// key names are matched ignoring case, "F1" and "f1" are the same key.
+fn normalize(key: &str) -> String {
+    key.trim().to_lowercase()
+}
+
+impl Bindings {
+    pub fn new() -> Self {
+        Self {
+            binds: BTreeMap::new(),
+            saved: BTreeMap::new(),
+            persistent: true,
+            changed: false,
+        }
+    }
+
+    /// binds command to key, replacing whatever it was bound to before.
+    pub fn bind(&mut self, key: &str, command: &str) -> Result<(), String> {
+        let key = normalize(key);
+        if key.is_empty() {
+            return Err(String::from("key name is empty"));
+        }
+        if command.trim().is_empty() {
+            return Err(String::from("command is empty"));
+        }
+        // the script bindings are saved to quotes both, so neither can hold a quote.
+        if key.contains('"') || command.contains('"') {
+            return Err(String::from("bindings can't contain quotes"));
+        }
+        let command = String::from(command.trim());
+        if self.persistent {
+            self.changed |= self.saved.get(&key) != Some(&command);
+            self.saved.insert(key.clone(), command.clone());
+        }
+        self.binds.insert(key, command);
+        Ok(())
+    }
+
+    /// removes a key's binding, false if it wasn't bound.
+    pub fn unbind(&mut self, key: &str) -> bool {
+        let key = normalize(key);
+        if self.persistent {
+            self.changed |= self.saved.remove(&key).is_some();
+        }
+        self.binds.remove(&key).is_some()
+    }
+
+    pub fn unbind_all(&mut self) {
+        if self.persistent {
+            self.changed |= !self.saved.is_empty();
+            self.saved.clear();
+        }
+        self.binds.clear();
+    }
+
+    /// whether changes from now on are saved, or only last this run.
+    pub fn set_persistent(&mut self, persistent: bool) {
+        self.persistent = persistent;
+    }
+
+    pub fn command_for(&self, key: &str) -> Option<&str> {
+        self.binds.get(&normalize(key)).map(|c| c.as_str())
+    }
+
+    /// every (key, command) pair sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.binds.iter().map(|(k, c)| (k.as_str(), c.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.binds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.binds.is_empty()
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// the saved bindings match what's on disk, e.g. just after loading them.
+    pub fn mark_saved(&mut self) {
+        self.changed = false;
+    }
+
+    /// a console script that recreates the saved bindings when exec'd.
+    pub fn to_script(&self) -> String {
+        let mut out = String::from("# rasteroids key bindings, run with exec\nunbindall\n");
+        for (key, command) in self.saved.iter() {
+            out += &format!("bind \"{}\" \"{}\"\n", key, command);
+        }
+        out
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_script())
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        self.mark_saved();
+        Ok(())
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bind_and_unbind() {
+        let mut bindings = Bindings::new();
+        assert!(!bindings.is_changed());
+        bindings.bind("F", "spawn asteroid 10 10 5").unwrap();
+        assert_eq!(bindings.command_for("f"), Some("spawn asteroid 10 10 5"));
+        bindings.bind("f", "god").unwrap();
+        assert_eq!(bindings.command_for("F"), Some("god"));
+        assert_eq!(bindings.len(), 1);
+        assert!(bindings.is_changed());
+
+        assert!(bindings.bind("g", "  ").is_err());
+        assert!(bindings.bind("g", "say \"hi\"").is_err());
+
+        assert!(bindings.unbind("F"));
+        assert!(!bindings.unbind("F"));
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn test_script() {
+        let mut bindings = Bindings::new();
+        bindings.bind("Keypad 1", "give lives 1").unwrap();
+        bindings.bind("g", "god").unwrap();
+        assert_eq!(
+            bindings.to_script(),
+            "# rasteroids key bindings, run with exec\nunbindall\n\
+             bind \"g\" \"god\"\nbind \"keypad 1\" \"give lives 1\"\n"
+        );
+    }
+
+    #[test]
+    fn test_only_persistent_changes_saved() {
+        // loading the saved file leaves nothing to save.
+        let mut bindings = Bindings::new();
+        bindings.unbind_all();
+        bindings.bind("g", "god").unwrap();
+        bindings.mark_saved();
+        let loaded = bindings.to_script();
+
+        // autoexec style changes apply but never reach the file.
+        bindings.set_persistent(false);
+        bindings.unbind_all();
+        bindings.bind("h", "give lives 1").unwrap();
+        bindings.set_persistent(true);
+        assert!(!bindings.is_changed());
+        assert_eq!(bindings.to_script(), loaded);
+        assert_eq!(bindings.command_for("g"), None);
+        assert_eq!(bindings.command_for("h"), Some("give lives 1"));
+
+        // binding the same again isn't a change, a new one is.
+        bindings.bind("g", "god").unwrap();
+        assert!(!bindings.is_changed());
+        bindings.bind("j", "reset").unwrap();
+        assert!(bindings.is_changed());
+        assert!(!bindings.to_script().contains("give lives"));
+    }
+}
@@ -0,0 +1,336 @@
+/// lets bots written in other languages fly the ship.
+///
+/// the game spawns the bot as a child process and each tick writes one line
+/// to its stdin describing the world:
+///
+/// tick <n> score <s> world <w> <h> player <x> <y> <velocity> <direction>
+///     asteroids <count> (<x> <y> <velocity> <direction> <radius>)*
+///     bullets <count> (<x> <y> <velocity> <direction>)*
+///
+/// (all on a single line) and expects one line back on stdout with the tick it
+/// is answering followed by the input, "<tick> <rotation> <shoot> <thrusters>".
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::asteroids::{GameInput, GameState, MoveAblePos};
+use crate::controller::Controller;
+use crate::log;
+
+/// how long a bot gets to answer before the tick goes ahead without it.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+/// state lines waiting to be written before a bot that isn't reading them
+/// starts missing ticks.
+const STATE_QUEUE: usize = 4;
+/// how long a bot gets to exit by itself once its stdin is closed.
+const EXIT_GRACE: Duration = Duration::from_millis(100);
+
+/// builds the state line sent to the bot for the given tick, without the newline.
+pub fn format_state(tick: u64, game_state: &GameState) -> String {
+    fn push_movement(out: &mut String, m: &MoveAblePos) {
+        let _ = write!(
+            out,
+            " {} {} {} {}",
+            m.pos_x,
+            m.pos_y,
+            m.velocity(),
+            m.direction()
+        );
+    }
+
+    let (width, height) = game_state.world_size();
+    let mut out = format!(
+        "tick {} score {} world {} {} player",
+        tick, game_state.score, width, height
+    );
+    push_movement(&mut out, &game_state.player.rust_sux);
+
+    let _ = write!(out, " asteroids {}", game_state.asteroids.len());
+    for ast in game_state.asteroids.iter() {
+        push_movement(&mut out, ast.movement());
+        let _ = write!(out, " {}", ast.bounding_box().radius);
+    }
+
+    let _ = write!(out, " bullets {}", game_state.bullets().len());
+    for bullet in game_state.bullets() {
+        push_movement(&mut out, bullet.movement());
+    }
+    out
+}
+
+/// parses a bot response line into the tick it answers and the input.
+pub fn parse_response(line: &str) -> Result<(u64, GameInput), String> {
+    let line = line.trim();
+    let (tick, rest) = match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], &line[i..]),
+        None => return Err(format!("malformed response '{}'", line)),
+    };
+    let tick = tick
+        .parse()
+        .map_err(|_| format!("invalid tick '{}' in '{}'", tick, line))?;
+    Ok((tick, rest.parse()?))
+}
+
+/// Controller backed by an external process speaking the line protocol.
+/// slow, silent or broken bots never stall the game, a tick they fail to
+/// answer is played with an idle input and counted as a fault.
+pub struct ExternalController {
+    child: Child,
+    /// state lines for the writer thread, None once the bot is gone.
+    states: Option<SyncSender<String>>,
+    responses: Receiver<String>,
+    timeout: Duration,
+    tick: u64,
+    faults: usize,
+}
+
+impl ExternalController {
+    /// spawns the bot, command is split on whitespace into program and arguments.
+    pub fn spawn(command: &str, timeout: Duration) -> Result<Self, String> {
+        let mut parts = command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| String::from("empty bot command"))?;
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to start bot '{}': {}", command, e))?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| String::from("bot has no stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| String::from("bot has no stdout"))?;
+
+        // reading is done on its own thread so a bot that never answers can be timed out.
+        let (sender, responses) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(l) => {
+                        if sender.send(l).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        // and writing too, so a bot that stops reading can't block on a full pipe.
+        let (states, pending) = mpsc::sync_channel::<String>(STATE_QUEUE);
+        thread::spawn(move || {
+            for line in pending {
+                if writeln!(stdin, "{}", line)
+                    .and_then(|_| stdin.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            states: Some(states),
+            responses,
+            timeout,
+            tick: 0,
+            faults: 0,
+        })
+    }
+
+    /// number of ticks the bot failed to answer correctly in time.
+    pub fn faults(&self) -> usize {
+        self.faults
+    }
+
+    fn fault(&mut self, reason: &str) -> GameInput {
+        log::warn("bot", format!("fault on tick {}: {}", self.tick, reason));
+        self.faults += 1;
+        GameInput::default()
+    }
+
+    /// the bot is gone for good, warned about once and idle from then on.
+    fn disconnect(&mut self, reason: &str) -> GameInput {
+        self.states = None;
+        self.fault(reason)
+    }
+
+    /// queues the state for the writer thread. a bot too far behind to
+    /// take it misses the tick rather than holding up the game.
+    fn send_state(&mut self, game_state: &GameState) -> Result<(), GameInput> {
+        let states = match self.states.as_ref() {
+            Some(s) => s,
+            None => {
+                self.faults += 1;
+                return Err(GameInput::default());
+            }
+        };
+        match states.try_send(format_state(self.tick, game_state)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(self.fault("not reading, state dropped")),
+            Err(TrySendError::Disconnected(_)) => Err(self.disconnect("bot stopped reading")),
+        }
+    }
+}
+
+impl Controller for ExternalController {
+    fn decide(&mut self, game_state: &GameState) -> GameInput {
+        self.tick += 1;
+        if let Err(input) = self.send_state(game_state) {
+            return input;
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.responses.recv_timeout(remaining) {
+                Ok(l) => l,
+                Err(RecvTimeoutError::Timeout) => return self.fault("timed out"),
+                Err(RecvTimeoutError::Disconnected) => return self.disconnect("bot exited"),
+            };
+
+            match parse_response(&line) {
+                // answers to ticks that already timed out are stale, skip them.
+                Ok((tick, _)) if tick < self.tick => continue,
+                Ok((tick, input)) if tick == self.tick => return input,
+                Ok((tick, _)) => return self.fault(&format!("answered future tick {}", tick)),
+                Err(e) => return self.fault(&e),
+            }
+        }
+    }
+}
+
+impl Drop for ExternalController {
+    fn drop(&mut self) {
+        // closing stdin lets well behaved bots exit on their own, anything
+        // still running after a moment is killed.
+        self.states = None;
+        let deadline = Instant::now() + EXIT_GRACE;
+        while Instant::now() < deadline {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(Duration::from_millis(5)),
+                _ => return,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::asteroids::game_init;
+
+    fn echo_bot() -> String {
+        format!(
+            "sh {}/resources/bots/echo_bot.sh",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    #[test]
+    fn test_state_line_counts() {
+        let game_state = game_init();
+        let line = format_state(7, &game_state);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        assert_eq!(&tokens[..2], &["tick", "7"]);
+
+        let asteroid_index = tokens.iter().position(|t| *t == "asteroids").unwrap();
+        let count: usize = tokens[asteroid_index + 1].parse().unwrap();
+        assert_eq!(count, game_state.asteroids.len());
+        assert_eq!(tokens[asteroid_index + 2 + count * 5], "bullets");
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let (tick, input) = parse_response("12 -1 1 0\n").unwrap();
+        assert_eq!(tick, 12);
+        assert_eq!(input.rotation, -1.0);
+        assert!(input.shoot);
+        assert!(!input.thrusters);
+
+        assert!(parse_response("12").is_err());
+        assert!(parse_response("twelve 0 0 0").is_err());
+        assert!(parse_response("12 0 yes 0").is_err());
+    }
+
+    #[test]
+    fn test_echo_bot() {
+        let game_state = game_init();
+        let mut bot = ExternalController::spawn(&echo_bot(), Duration::from_secs(5)).unwrap();
+        for _ in 0..3 {
+            assert_eq!(bot.decide(&game_state), GameInput::default());
+        }
+        assert_eq!(bot.faults(), 0);
+    }
+
+    #[test]
+    fn test_malformed_bot() {
+        let script = std::env::temp_dir().join(format!(
+            "rasteroids_malformed_bot_{}.sh",
+            std::process::id()
+        ));
+        std::fs::write(&script, "while read -r line; do echo nonsense; done\n").unwrap();
+
+        let game_state = game_init();
+        let mut bot =
+            ExternalController::spawn(&format!("sh {}", script.display()), Duration::from_secs(5))
+                .unwrap();
+        assert_eq!(bot.decide(&game_state), GameInput::default());
+        assert_eq!(bot.decide(&game_state), GameInput::default());
+        assert_eq!(bot.faults(), 2);
+        std::fs::remove_file(&script).unwrap();
+    }
+
+    #[test]
+    fn test_missing_bot() {
+        assert!(ExternalController::spawn("definitely-not-a-bot", DEFAULT_TIMEOUT).is_err());
+        assert!(ExternalController::spawn("", DEFAULT_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn test_silent_bot_times_out() {
+        let game_state = game_init();
+        let mut bot = ExternalController::spawn("sleep 5", Duration::from_millis(50)).unwrap();
+        let start = Instant::now();
+        assert_eq!(bot.decide(&game_state), GameInput::default());
+        assert_eq!(bot.faults(), 1);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_bot_not_reading_never_blocks() {
+        // enough state lines to fill the pipe to a bot that never reads them.
+        let game_state = game_init();
+        let mut bot = ExternalController::spawn("sleep 30", Duration::from_millis(1)).unwrap();
+        let start = Instant::now();
+        for _ in 0..3000 {
+            assert_eq!(bot.decide(&game_state), GameInput::default());
+        }
+        assert_eq!(bot.faults(), 3000);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_exited_bot_stays_disconnected() {
+        let game_state = game_init();
+        let mut bot = ExternalController::spawn("true", Duration::from_secs(5)).unwrap();
+        for _ in 0..5 {
+            assert_eq!(bot.decide(&game_state), GameInput::default());
+        }
+        assert!(bot.states.is_none());
+        assert_eq!(bot.faults(), 5);
+    }
+}
@@ -6,6 +6,8 @@ use sdl2::render::{Canvas, Texture, TextureCreator};
 use sdl2::surface::Surface;
 use sdl2::video::{Window, WindowContext};
 
+use crate::log;
+
 fn circle_formula(x: u32, y: u32) -> u32 {
     return ((x * x) + (y * y)).into();
 }
@@ -54,7 +56,7 @@ pub fn create_circle_texture<'a>(
     match text {
         Ok(_r) => (),
         Err(r) => {
-            println!("create circle texture error!: {}", r);
+            log::error("render", format!("create circle texture error!: {}", r));
         }
     };
 
@@ -0,0 +1,991 @@
+/// console commands, typed in as "name arg arg ..." and run against the live game.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use std::fs;
+use std::path::Path;
+
+use crate::asteroids::{self, Asteroid, GameState};
+use crate::bindings::Bindings;
+use crate::cvars::{self, CvarRegistry};
+use crate::log::{self, Level};
+
+/// script run at startup if it exists, e.g. to set up a debug scenario.
+pub const AUTOEXEC_FILE: &str = "autoexec.cfg";
+
+/// scripts can exec other scripts, this stops one that execs itself.
+const MAX_EXEC_DEPTH: usize = 8;
+/// highest wave the wave command jumps to, later waves have more asteroids
+/// than the game can keep up with.
+const MAX_WAVE: u32 = 1000;
+
+/// everything a command is allowed to touch while it runs.
+pub struct CommandContext<'a> {
+    pub game_state: &'a mut GameState,
+    pub cvars: &'a mut CvarRegistry,
+    pub bindings: &'a mut Bindings,
+    /// lines to show back to the user.
+    output: Vec<String>,
+    /// messages to pop up over the game, e.g. from toast.
+    notifications: Vec<String>,
+    /// how many scripts deep the current command is being run from.
+    exec_depth: usize,
+}
+
+impl<'a> CommandContext<'a> {
+    pub fn new(
+        game_state: &'a mut GameState,
+        cvars: &'a mut CvarRegistry,
+        bindings: &'a mut Bindings,
+    ) -> Self {
+        Self {
+            game_state,
+            cvars,
+            bindings,
+            output: Vec::new(),
+            notifications: Vec::new(),
+            exec_depth: 0,
+        }
+    }
+
+    /// starts a new game with the given seed, keeping the current cvars.
+    fn restart(&mut self, seed: u64) {
+        *self.game_state = asteroids::game_init_seeded(seed);
+        self.cvars.apply(&mut self.game_state.tuning);
+    }
+
+    pub fn print<S: Into<String>>(&mut self, line: S) {
+        self.output.push(line.into());
+    }
+
+    /// lines printed so far, leaving the context empty.
+    pub fn take_output(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.output)
+    }
+
+    pub fn notify<S: Into<String>>(&mut self, message: S) {
+        self.notifications.push(message.into());
+    }
+
+    /// messages to pop up so far, leaving none behind.
+    pub fn take_notifications(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notifications)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    /// no command registered with this name.
+    Unknown(String),
+    /// wrong number of arguments, holds the command's usage string.
+    Usage(String),
+    /// an argument couldn't be parsed as the type the command needed.
+    InvalidArgument {
+        name: &'static str,
+        value: String,
+        usage: String,
+    },
+    /// input couldn't be split into words, e.g. an unclosed quote.
+    Syntax(String),
+    /// the command ran but couldn't do what it was asked.
+    Failed(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Unknown(name) => write!(f, "unknown command '{}'", name),
+            CommandError::Usage(usage) => write!(f, "usage: {}", usage),
+            CommandError::InvalidArgument { name, value, usage } => {
+                write!(f, "invalid {} '{}', usage: {}", name, value, usage)
+            }
+            CommandError::Syntax(msg) => write!(f, "{}", msg),
+            CommandError::Failed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// arguments given to a command, not including the command name.
+pub struct Args<'a> {
+    words: &'a [String],
+    usage: &'static str,
+    registry: &'a CommandRegistry,
+}
+
+impl<'a> Args<'a> {
+    /// registry the command was run from.
+    pub fn registry(&self) -> &'a CommandRegistry {
+        self.registry
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// raw argument, usage error if it isn't there.
+    pub fn str(&self, index: usize) -> Result<&'a str, CommandError> {
+        self.words
+            .get(index)
+            .map(|s| s.as_str())
+            .ok_or_else(|| CommandError::Usage(String::from(self.usage)))
+    }
+
+    /// argument parsed as T, name is used in the error message if it doesn't parse.
+    pub fn get<T: FromStr>(&self, index: usize, name: &'static str) -> Result<T, CommandError> {
+        let value = self.str(index)?;
+        value.parse().map_err(|_| CommandError::InvalidArgument {
+            name,
+            value: String::from(value),
+            usage: String::from(self.usage),
+        })
+    }
+
+    /// a number argument, nan and inf parse as f64 but are refused here.
+    pub fn number(&self, index: usize, name: &'static str) -> Result<f64, CommandError> {
+        let value: f64 = self.get(index, name)?;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(CommandError::InvalidArgument {
+                name,
+                value: String::from(self.str(index)?),
+                usage: String::from(self.usage),
+            })
+        }
+    }
+
+    /// like get but falls back to default when the argument wasn't given.
+    pub fn get_or<T: FromStr>(
+        &self,
+        index: usize,
+        name: &'static str,
+        default: T,
+    ) -> Result<T, CommandError> {
+        if index < self.words.len() {
+            self.get(index, name)
+        } else {
+            Ok(default)
+        }
+    }
+
+    /// every argument from index on joined back together with spaces.
+    pub fn rest(&self, index: usize) -> String {
+        self.words.get(index..).unwrap_or(&[]).join(" ")
+    }
+}
+
+pub type Handler = fn(&mut CommandContext, &Args) -> Result<(), CommandError>;
+
+pub struct Command {
+    pub name: &'static str,
+    /// shown on usage errors, e.g. "give lives <n>".
+    pub usage: &'static str,
+    pub help: &'static str,
+    pub min_args: usize,
+    /// None for commands that take any number of trailing arguments.
+    pub max_args: Option<usize>,
+    pub handler: Handler,
+}
+
+/// Splits a line into words on whitespace, double quotes group words together.
+pub fn tokenize(line: &str) -> Result<Vec<String>, CommandError> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut in_quotes = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(CommandError::Syntax(String::from("unclosed quote")));
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, Command>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self {
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// registry with all of the debug commands the game ships with.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for command in builtin_commands() {
+            registry.register(command);
+        }
+        registry
+    }
+
+    /// adds a command, replacing any existing one with the same name.
+    pub fn register(&mut self, command: Command) {
+        self.commands.insert(command.name, command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.get(name)
+    }
+
+    /// registered command names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.keys().copied()
+    }
+
+    /// runs a single line of input, blank lines do nothing.
+    pub fn execute(&self, ctx: &mut CommandContext, line: &str) -> Result<(), CommandError> {
+        let words = tokenize(line)?;
+        let (name, rest) = match words.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        let command = self
+            .commands
+            .get(name.as_str())
+            .ok_or_else(|| CommandError::Unknown(name.clone()))?;
+
+        let too_many = command.max_args.is_some_and(|max| rest.len() > max);
+        if rest.len() < command.min_args || too_many {
+            return Err(CommandError::Usage(String::from(command.usage)));
+        }
+
+        let args = Args {
+            words: rest,
+            usage: command.usage,
+            registry: self,
+        };
+        (command.handler)(ctx, &args)
+    }
+
+    /// runs each line of a script, skipping blank lines and # comments. a bad
+    /// line is reported with name and its line number and the rest still runs.
+    pub fn exec_script(
+        &self,
+        ctx: &mut CommandContext,
+        name: &str,
+        script: &str,
+    ) -> Result<(), CommandError> {
+        if ctx.exec_depth >= MAX_EXEC_DEPTH {
+            return Err(CommandError::Failed(format!(
+                "{}: scripts nested more than {} deep",
+                name, MAX_EXEC_DEPTH
+            )));
+        }
+        ctx.exec_depth += 1;
+        for (line_number, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(e) = self.execute(ctx, line) {
+                ctx.print(format!("{}:{}: {}", name, line_number + 1, e));
+            }
+        }
+        ctx.exec_depth -= 1;
+        Ok(())
+    }
+
+    /// runs a line and returns everything it printed, errors included.
+    pub fn execute_to_lines(&self, ctx: &mut CommandContext, line: &str) -> Vec<String> {
+        if let Err(e) = self.execute(ctx, line) {
+            ctx.print(e.to_string());
+        }
+        ctx.take_output()
+    }
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn builtin_commands() -> Vec<Command> {
+    vec![
+        Command {
+            name: "help",
+            usage: "help [command]",
+            help: "list commands or show how to use one",
+            min_args: 0,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let registry = args.registry();
+                if args.is_empty() {
+                    for command in registry.commands.values() {
+                        ctx.print(format!("{} - {}", command.usage, command.help));
+                    }
+                    return Ok(());
+                }
+                let name = args.str(0)?;
+                match registry.get(name) {
+                    Some(command) => {
+                        ctx.print(format!("{} - {}", command.usage, command.help));
+                        Ok(())
+                    }
+                    None => Err(CommandError::Unknown(String::from(name))),
+                }
+            },
+        },
+        Command {
+            name: "spawn",
+            usage: "spawn asteroid <x> <y> <size>",
+            help: "drop a stationary asteroid into the world",
+            min_args: 4,
+            max_args: Some(4),
+            handler: |ctx, args| {
+                if args.str(0)? != "asteroid" {
+                    return Err(CommandError::InvalidArgument {
+                        name: "entity",
+                        value: String::from(args.str(0)?),
+                        usage: String::from(args.usage),
+                    });
+                }
+                let x = args.number(1, "x")?;
+                let y = args.number(2, "y")?;
+                let size = args.number(3, "size")?;
+                if size <= 0.0 {
+                    return Err(CommandError::Failed(String::from("size must be above 0")));
+                }
+                ctx.game_state
+                    .asteroids
+                    .push(Asteroid::new(x, y, 0.0, 0.0, size));
+                ctx.print(format!("spawned asteroid at {} {}", x, y));
+                Ok(())
+            },
+        },
+        Command {
+            name: "god",
+            usage: "god",
+            help: "toggle asteroids not hurting the player",
+            min_args: 0,
+            max_args: Some(0),
+            handler: |ctx, _| {
+                ctx.game_state.god_mode = !ctx.game_state.god_mode;
+                let state = if ctx.game_state.god_mode { "on" } else { "off" };
+                ctx.print(format!("god mode {}", state));
+                Ok(())
+            },
+        },
+        Command {
+            name: "give",
+            usage: "give lives <n>",
+            help: "add extra lives",
+            min_args: 2,
+            max_args: Some(2),
+            handler: |ctx, args| {
+                if args.str(0)? != "lives" {
+                    return Err(CommandError::InvalidArgument {
+                        name: "item",
+                        value: String::from(args.str(0)?),
+                        usage: String::from(args.usage),
+                    });
+                }
+                let n: u32 = args.get(1, "n")?;
+                ctx.game_state.lives = ctx.game_state.lives.saturating_add(n);
+                ctx.print(format!("lives: {}", ctx.game_state.lives));
+                Ok(())
+            },
+        },
+        Command {
+            name: "wave",
+            usage: "wave <n>",
+            help: "jump straight to a wave",
+            min_args: 1,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let wave: u32 = args.get(0, "n")?;
+                if wave == 0 || wave > MAX_WAVE {
+                    return Err(CommandError::Failed(format!(
+                        "wave must be between 1 and {}",
+                        MAX_WAVE
+                    )));
+                }
+                asteroids::start_wave(ctx.game_state, wave);
+                ctx.print(format!("wave {}", wave));
+                Ok(())
+            },
+        },
+        Command {
+            name: "seed",
+            usage: "seed [n]",
+            help: "show the seed, or restart the game with a new one",
+            min_args: 0,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                if args.is_empty() {
+                    let seed = ctx.game_state.seed;
+                    ctx.print(format!("seed: {}", seed));
+                    return Ok(());
+                }
+                let seed: u64 = args.get(0, "n")?;
+                ctx.restart(seed);
+                ctx.print(format!("restarted with seed {}", seed));
+                Ok(())
+            },
+        },
+        Command {
+            name: "reset",
+            usage: "reset",
+            help: "restart the game with the current seed",
+            min_args: 0,
+            max_args: Some(0),
+            handler: |ctx, _| {
+                let seed = ctx.game_state.seed;
+                ctx.restart(seed);
+                ctx.print(format!("restarted with seed {}", seed));
+                Ok(())
+            },
+        },
+        Command {
+            name: "set",
+            usage: "set <cvar> <value>",
+            help: "change a cvar",
+            min_args: 2,
+            max_args: Some(2),
+            handler: |ctx, args| {
+                let name = args.str(0)?;
+                ctx.cvars
+                    .set(name, args.str(1)?)
+                    .map_err(CommandError::Failed)?;
+                ctx.cvars.apply(&mut ctx.game_state.tuning);
+                let value = ctx.cvars.get(name).map(|c| c.value());
+                if let Some(value) = value {
+                    ctx.print(format!("{} {}", name, value));
+                }
+                Ok(())
+            },
+        },
+        Command {
+            name: "get",
+            usage: "get <cvar>",
+            help: "show a cvar's value, type and range",
+            min_args: 1,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let name = args.str(0)?;
+                let line = ctx
+                    .cvars
+                    .get(name)
+                    .map(|c| c.describe())
+                    .ok_or_else(|| CommandError::Failed(format!("unknown cvar '{}'", name)))?;
+                ctx.print(line);
+                Ok(())
+            },
+        },
+        Command {
+            name: "toggle",
+            usage: "toggle <cvar>",
+            help: "flip an on/off cvar",
+            min_args: 1,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let name = args.str(0)?;
+                let value = ctx.cvars.get_bool(name).ok_or_else(|| {
+                    CommandError::Failed(format!("{} is not an on/off cvar", name))
+                })?;
+                let flipped = if value { "off" } else { "on" };
+                ctx.cvars.set(name, flipped).map_err(CommandError::Failed)?;
+                ctx.print(format!("{} {}", name, flipped));
+                Ok(())
+            },
+        },
+        Command {
+            name: "cvars",
+            usage: "cvars",
+            help: "list every cvar",
+            min_args: 0,
+            max_args: Some(0),
+            handler: |ctx, _| {
+                let lines: Vec<String> = ctx.cvars.iter().map(|c| c.describe()).collect();
+                for line in lines {
+                    ctx.print(line);
+                }
+                Ok(())
+            },
+        },
+        Command {
+            name: "save_config",
+            usage: "save_config [file]",
+            help: "write the cvars out to a config file",
+            min_args: 0,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let path = args.get_or(0, "file", String::from(cvars::CONFIG_FILE))?;
+                ctx.cvars
+                    .save(Path::new(&path))
+                    .map_err(CommandError::Failed)?;
+                ctx.print(format!("saved {}", path));
+                Ok(())
+            },
+        },
+        Command {
+            name: "load_config",
+            usage: "load_config [file]",
+            help: "read cvars back in from a config file",
+            min_args: 0,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let path = args.get_or(0, "file", String::from(cvars::CONFIG_FILE))?;
+                let errors = ctx
+                    .cvars
+                    .load(Path::new(&path))
+                    .map_err(CommandError::Failed)?;
+                ctx.cvars.apply(&mut ctx.game_state.tuning);
+                for e in errors {
+                    ctx.print(format!("{}: {}", path, e));
+                }
+                ctx.print(format!("loaded {}", path));
+                Ok(())
+            },
+        },
+        Command {
+            name: "exec",
+            usage: "exec <file>",
+            help: "run a file of console commands, one per line",
+            min_args: 1,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let path = args.str(0)?;
+                let script = fs::read_to_string(path)
+                    .map_err(|e| CommandError::Failed(format!("failed to read {}: {}", path, e)))?;
+                args.registry().exec_script(ctx, path, &script)
+            },
+        },
+        Command {
+            name: "bind",
+            usage: "bind <key> [command]",
+            help: "run a command when a key is pressed, or show what a key runs",
+            min_args: 1,
+            max_args: None,
+            handler: |ctx, args| {
+                let key = args.str(0)?;
+                if args.len() == 1 {
+                    let line = match ctx.bindings.command_for(key) {
+                        Some(command) => format!("{} runs \"{}\"", key, command),
+                        None => format!("{} is not bound", key),
+                    };
+                    ctx.print(line);
+                    return Ok(());
+                }
+                let command = args.rest(1);
+                ctx.bindings
+                    .bind(key, &command)
+                    .map_err(CommandError::Failed)?;
+                ctx.print(format!("{} runs \"{}\"", key, command));
+                Ok(())
+            },
+        },
+        Command {
+            name: "unbind",
+            usage: "unbind <key>",
+            help: "remove a key's binding",
+            min_args: 1,
+            max_args: Some(1),
+            handler: |ctx, args| {
+                let key = args.str(0)?;
+                if !ctx.bindings.unbind(key) {
+                    return Err(CommandError::Failed(format!("{} is not bound", key)));
+                }
+                ctx.print(format!("unbound {}", key));
+                Ok(())
+            },
+        },
+        Command {
+            name: "unbindall",
+            usage: "unbindall",
+            help: "remove every key binding",
+            min_args: 0,
+            max_args: Some(0),
+            handler: |ctx, _| {
+                ctx.bindings.unbind_all();
+                Ok(())
+            },
+        },
+        Command {
+            name: "binds",
+            usage: "binds",
+            help: "list every key binding",
+            min_args: 0,
+            max_args: Some(0),
+            handler: |ctx, _| {
+                let lines: Vec<String> = ctx
+                    .bindings
+                    .iter()
+                    .map(|(key, command)| format!("{} runs \"{}\"", key, command))
+                    .collect();
+                for line in lines {
+                    ctx.print(line);
+                }
+                Ok(())
+            },
+        },
+        Command {
+            name: "log",
+            usage: "log [category|all] [level]",
+            help: "show or set which log levels are shown, all sets every category",
+            min_args: 0,
+            max_args: Some(2),
+            handler: |ctx, args| {
+                if args.is_empty() {
+                    let (default, levels) = log::with_logger(|logger| {
+                        let levels: Vec<String> = log::CATEGORIES
+                            .iter()
+                            .map(|c| format!("{} {}", c, logger.level(c)))
+                            .collect();
+                        (logger.default_level(), levels)
+                    });
+                    ctx.print(format!("all {}", default));
+                    for line in levels {
+                        ctx.print(line);
+                    }
+                    return Ok(());
+                }
+                let category = args.str(0)?;
+                if category != "all" && !log::CATEGORIES.contains(&category) {
+                    return Err(CommandError::InvalidArgument {
+                        name: "category",
+                        value: String::from(category),
+                        usage: String::from(args.usage),
+                    });
+                }
+                if args.len() == 1 {
+                    let level = log::with_logger(|logger| {
+                        if category == "all" {
+                            logger.default_level()
+                        } else {
+                            logger.level(category)
+                        }
+                    });
+                    ctx.print(format!("{} {}", category, level));
+                    return Ok(());
+                }
+                let level: Level = args.get(1, "level")?;
+                log::with_logger(|logger| {
+                    if category == "all" {
+                        logger.set_default_level(level);
+                        for c in log::CATEGORIES {
+                            logger.clear_level(c);
+                        }
+                        Ok(())
+                    } else {
+                        logger.set_level(category, level)
+                    }
+                })
+                .map_err(CommandError::Failed)?;
+                ctx.print(format!("{} {}", category, level));
+                Ok(())
+            },
+        },
+        Command {
+            name: "toast",
+            usage: "toast <message>",
+            help: "pop a message up over the game",
+            min_args: 1,
+            max_args: None,
+            handler: |ctx, args| {
+                ctx.notify(args.rest(0));
+                Ok(())
+            },
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroids::game_init_seeded;
+
+    /// calls test with the built in commands and a context over game_state.
+    fn with_game<R>(
+        game_state: &mut GameState,
+        test: impl FnOnce(&CommandRegistry, &mut CommandContext) -> R,
+    ) -> R {
+        let mut cvars = CvarRegistry::with_game_cvars();
+        let mut bindings = Bindings::new();
+        test(
+            &CommandRegistry::with_builtins(),
+            &mut CommandContext::new(game_state, &mut cvars, &mut bindings),
+        )
+    }
+
+    /// same again with a fresh game.
+    fn with_context<R>(test: impl FnOnce(&CommandRegistry, &mut CommandContext) -> R) -> R {
+        with_game(&mut game_init_seeded(1), test)
+    }
+
+    fn run(game_state: &mut GameState, line: &str) -> Result<(), CommandError> {
+        with_game(game_state, |registry, ctx| registry.execute(ctx, line))
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("  spawn  asteroid 1 2 ").unwrap(),
+            vec!["spawn", "asteroid", "1", "2"]
+        );
+        assert_eq!(
+            tokenize("bind f \"give lives 1\"").unwrap(),
+            vec!["bind", "f", "give lives 1"]
+        );
+        assert_eq!(tokenize("say \"\"").unwrap(), vec!["say", ""]);
+        assert!(tokenize("").unwrap().is_empty());
+        assert_eq!(
+            tokenize("bind \"f"),
+            Err(CommandError::Syntax(String::from("unclosed quote")))
+        );
+    }
+
+    #[test]
+    fn test_spawn_asteroid() {
+        let mut game_state = game_init_seeded(1);
+        let before = game_state.asteroids.len();
+        run(&mut game_state, "spawn asteroid 10 20.5 4").unwrap();
+        assert_eq!(game_state.asteroids.len(), before + 1);
+        let b = game_state.asteroids.last().unwrap().bounding_box();
+        assert_eq!((b.pos_x, b.pos_y, b.radius), (10.0, 20.5, 4.0));
+
+        for line in &[
+            "spawn asteroid nan 1 1",
+            "spawn asteroid 1 inf 1",
+            "spawn asteroid 1 1 nan",
+        ] {
+            assert!(matches!(
+                run(&mut game_state, line),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+        }
+        assert_eq!(game_state.asteroids.len(), before + 1);
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        let mut game_state = game_init_seeded(1);
+        assert_eq!(
+            run(&mut game_state, "spawn asteroid 10"),
+            Err(CommandError::Usage(String::from(
+                "spawn asteroid <x> <y> <size>"
+            )))
+        );
+        match run(&mut game_state, "give lives lots") {
+            Err(CommandError::InvalidArgument { name, value, .. }) => {
+                assert_eq!(name, "n");
+                assert_eq!(value, "lots");
+            }
+            other => panic!("expected invalid argument got {:?}", other),
+        }
+        assert_eq!(
+            run(&mut game_state, "fly"),
+            Err(CommandError::Unknown(String::from("fly")))
+        );
+        assert_eq!(run(&mut game_state, "   "), Ok(()));
+    }
+
+    #[test]
+    fn test_game_commands() {
+        let mut game_state = game_init_seeded(1);
+        run(&mut game_state, "god").unwrap();
+        assert!(game_state.god_mode);
+
+        let lives = game_state.lives;
+        run(&mut game_state, "give lives 2").unwrap();
+        assert_eq!(game_state.lives, lives + 2);
+
+        run(&mut game_state, "wave 4").unwrap();
+        assert_eq!(game_state.wave, 4);
+
+        run(&mut game_state, "seed 99").unwrap();
+        assert_eq!(game_state.seed, 99);
+        assert_eq!(game_state.wave, 1);
+        assert!(!game_state.god_mode);
+
+        game_state.score = 500;
+        run(&mut game_state, "reset").unwrap();
+        assert_eq!(game_state.score, 0);
+        assert_eq!(game_state.seed, 99);
+    }
+
+    #[test]
+    fn test_cvar_commands() {
+        with_context(|registry, ctx| {
+            assert_eq!(
+                registry.execute_to_lines(ctx, "set fire_cooldown 3"),
+                vec!["fire_cooldown 3"]
+            );
+            assert_eq!(ctx.game_state.tuning.fire_cooldown, 3);
+            assert_eq!(
+                registry.execute_to_lines(ctx, "set fire_cooldown -1"),
+                vec!["fire_cooldown must be between 0 and 1000"]
+            );
+
+            // cvars stick around through a reset.
+            registry.execute(ctx, "reset").unwrap();
+            assert_eq!(ctx.game_state.tuning.fire_cooldown, 3);
+            assert_eq!(ctx.cvars.get_f64("fire_cooldown"), Some(3.0));
+        });
+    }
+
+    #[test]
+    fn test_help() {
+        with_context(|registry, ctx| {
+            let lines = registry.execute_to_lines(ctx, "help");
+            assert_eq!(lines.len(), registry.names().count());
+            assert_eq!(
+                registry.execute_to_lines(ctx, "help god"),
+                vec!["god - toggle asteroids not hurting the player"]
+            );
+        });
+    }
+
+    #[test]
+    fn test_output_lines() {
+        with_context(|registry, ctx| {
+            assert_eq!(registry.execute_to_lines(ctx, "seed"), vec!["seed: 1"]);
+            assert_eq!(
+                registry.execute_to_lines(ctx, "wave 0"),
+                vec!["wave must be between 1 and 1000"]
+            );
+            assert_eq!(
+                registry.execute_to_lines(ctx, "wave 100000000"),
+                vec!["wave must be between 1 and 1000"]
+            );
+            assert_eq!(ctx.game_state.wave, 1);
+        });
+    }
+
+    #[test]
+    fn test_log_command() {
+        with_context(|registry, ctx| {
+            assert_eq!(
+                registry.execute_to_lines(ctx, "log render warn"),
+                vec!["render warn"]
+            );
+            assert_eq!(
+                registry.execute_to_lines(ctx, "log render"),
+                vec!["render warn"]
+            );
+            assert!(matches!(
+                registry.execute(ctx, "log sound debug"),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+            assert!(matches!(
+                registry.execute(ctx, "log render loud"),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn test_exec_script() {
+        with_context(|registry, ctx| {
+            let lives = ctx.game_state.lives;
+            let script = "# debug setup\n\ngod\n  give lives 2\nfly away\nset thrust 5\n";
+            registry.exec_script(ctx, "test.cfg", script).unwrap();
+            assert_eq!(
+                ctx.take_output(),
+                vec![
+                    String::from("god mode on"),
+                    format!("lives: {}", lives + 2),
+                    String::from("test.cfg:5: unknown command 'fly'"),
+                    String::from("thrust 5"),
+                ]
+            );
+            assert!(ctx.game_state.god_mode);
+            assert_eq!(ctx.game_state.tuning.thrust, 5.0);
+        });
+    }
+
+    #[test]
+    fn test_exec_recursion_stops() {
+        let path = std::env::temp_dir().join(format!("rasteroids_exec_{}.cfg", std::process::id()));
+        let path_str = path.to_str().unwrap().to_string();
+        fs::write(&path, format!("give lives 1\nexec \"{}\"\n", path_str)).unwrap();
+
+        with_context(|registry, ctx| {
+            let lives = ctx.game_state.lives;
+            registry
+                .execute(ctx, &format!("exec \"{}\"", path_str))
+                .unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(ctx.game_state.lives, lives + MAX_EXEC_DEPTH as u32);
+            assert!(ctx
+                .take_output()
+                .iter()
+                .any(|line| line.contains("nested more than")));
+            assert!(registry.execute(ctx, "exec no_such_file.cfg").is_err());
+        });
+    }
+
+    #[test]
+    fn test_bind_commands() {
+        with_context(|registry, ctx| {
+            registry
+                .execute(ctx, "bind F spawn asteroid 1 2 3")
+                .unwrap();
+            registry
+                .execute(ctx, "bind g \"toggle console_pause\"")
+                .unwrap();
+            assert_eq!(ctx.bindings.command_for("f"), Some("spawn asteroid 1 2 3"));
+            assert!(registry.execute(ctx, "unbind h").is_err());
+
+            // the saved script brings back the same bindings.
+            let script = ctx.bindings.to_script();
+            registry.execute(ctx, "unbindall").unwrap();
+            assert!(ctx.bindings.is_empty());
+            registry.exec_script(ctx, "binds", &script).unwrap();
+            assert_eq!(ctx.bindings.command_for("g"), Some("toggle console_pause"));
+            assert_eq!(ctx.bindings.len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_toggle() {
+        with_context(|registry, ctx| {
+            registry.execute(ctx, "toggle console_pause").unwrap();
+            assert_eq!(ctx.cvars.get_bool("console_pause"), Some(false));
+            assert!(registry.execute(ctx, "toggle thrust").is_err());
+        });
+    }
+
+    #[test]
+    fn test_toast() {
+        with_context(|registry, ctx| {
+            assert!(registry
+                .execute_to_lines(ctx, "toast \"wave 5\" incoming")
+                .is_empty());
+            assert_eq!(ctx.take_notifications(), vec!["wave 5 incoming"]);
+            assert!(ctx.take_notifications().is_empty());
+            assert!(registry.execute(ctx, "toast").is_err());
+        });
+    }
+}
@@ -1,101 +1,290 @@
 use sdl2::rect::Rect;
 
+use crate::line_editor::{complete, History, LineEditor};
 use crate::widget::DrawableWidget;
-use crate::widget::Widget;
+use crate::widget::{EventResult, Widget};
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::pixels::Color;
 use sdl2::render::Canvas;
-use sdl2::surface::Surface;
-use sdl2::ttf::Font;
-use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::Window;
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::layout::Bounds;
+use crate::log::{Level, Record};
+use crate::mouse;
+use crate::text_cache::{FontId, TextCache};
+use crate::theme::Theme;
+use crate::tween::{Easing, Tween};
+use crate::utils::wrap_text;
+
+/// oldest lines are dropped once the buffer grows past this.
+const MAX_BUFFER_LINES: usize = 500;
+const MAX_HISTORY: usize = 100;
+/// gap in pixels between the console frame and its text.
+const PADDING: u32 = 4;
+const CARET_WIDTH: u32 = 2;
+/// seconds the console takes to slide down into view.
+const SLIDE_SECONDS: f32 = 0.2;
+/// buffer lines scrolled per notch of the mouse wheel.
+const WHEEL_LINES: isize = 3;
 
 /// Manages the state of input provided by the user as a collection of strings.
 /// provides some font handling and drawing to the screen.
-pub struct Console<'ttf, 'a, 'callback> {
-    current_string: String,
-    buffer: Vec<String>,
+pub struct Console<'ttf, 'tc, 'callback> {
+    /// line currently being typed.
+    input: LineEditor,
+    history: History,
+    /// output and entered lines, oldest first, capped at MAX_BUFFER_LINES.
+    buffer: VecDeque<(String, Color)>,
+    /// how many lines up from the bottom of the buffer the view is scrolled.
+    scroll: usize,
+
+    /// names offered when tab completing the first word of a line.
+    command_names: Vec<String>,
+    /// names offered when tab completing any later word.
+    argument_names: Vec<String>,
+
+    /// shared with everything else drawing text, so lines that haven't
+    /// changed aren't rendered again every frame.
+    text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+    font: FontId,
+    theme: Theme,
 
-    surface: Option<Surface<'a>>,
-    font: Font<'ttf, 'a>,
+    // height of a single line of text in pixels, from the font.
+    line_height: u32,
 
-    // width of a single character in pixels.
-    p_widget: u32,
-    // heigh to f asingle character in pixels.
-    char_height: u16,
+    /// vertical offset of the frame, slides from above the screen to 0.
+    slide: Tween,
 
     // width of the console frame in pixels.
     console_width: u32,
     // height of the console frame in pixels.
     console_height: u32,
+    /// where the frame was last drawn, for telling if the mouse is over it.
+    frame: Bounds,
 
     // need some sort of callback hook for when event should occur.
     /// callback function if defined
     enter_callback: &'callback dyn Fn(String) -> (),
 }
 
-impl<'ttf, 'a, 'callback> Console<'ttf, 'a, 'callback> {
+impl<'ttf, 'tc, 'callback> Console<'ttf, 'tc, 'callback> {
     pub fn new(
-        font_path: PathBuf,
-        ttf_c: &'ttf Sdl2TtfContext,
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
         enter_callback: &'callback dyn Fn(String) -> (),
     ) -> Self {
+        let font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.small_size)
+            .unwrap();
+        let line_height = text.borrow().line_height(font);
         Self {
-            current_string: String::new(),
-            buffer: Vec::new(),
-            surface: None,
-            font: ttf_c.load_font(font_path, 128).unwrap(),
-            p_widget: 30,
-            char_height: 30,
+            input: LineEditor::new(),
+            history: History::new(MAX_HISTORY),
+            buffer: VecDeque::new(),
+            scroll: 0,
+            command_names: Vec::new(),
+            argument_names: Vec::new(),
+            text,
+            font,
+            theme: theme.clone(),
+            line_height,
+            slide: Tween::fixed(0.0),
             console_width: 300,
             console_height: 400,
+            frame: Bounds::default(),
             enter_callback: enter_callback,
         }
     }
+
+    /// adds a line of output to the console, e.g. the result of a command.
+    pub fn print<S: Into<String>>(&mut self, line: S) {
+        self.print_colored(line, self.theme.console_text);
+    }
+
+    pub fn print_colored<S: Into<String>>(&mut self, line: S, color: Color) {
+        self.buffer.push_back((line.into(), color));
+        if self.buffer.len() > MAX_BUFFER_LINES {
+            self.buffer.pop_front();
+        }
+        // stay put on the same lines when scrolled back.
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.max_scroll());
+        }
+    }
+
+    /// adds a log record, colored by how serious it is.
+    pub fn print_record(&mut self, record: &Record) {
+        let color = match record.level {
+            Level::Debug => self.theme.debug,
+            Level::Info => self.theme.console_text,
+            Level::Warn => self.theme.warning,
+            Level::Error => self.theme.error,
+        };
+        self.print_colored(record.to_string(), color);
+    }
+
+    /// starts the console sliding down from above where it's drawn.
+    pub fn slide_in(&mut self) {
+        self.slide = Tween::new(
+            -(self.console_height as f32),
+            0.0,
+            SLIDE_SECONDS,
+            Easing::EaseOut,
+        );
+    }
+
+    /// size of the console frame in pixels.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.console_width = width;
+        self.console_height = height;
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    /// rendered width of text in pixels.
+    fn text_width(&self, text: &str) -> u32 {
+        self.text.borrow().text_width(self.font, text)
+    }
+
+    /// room left for text once the padding is taken off.
+    fn inner_width(&self) -> u32 {
+        self.console_width.saturating_sub(2 * PADDING)
+    }
+
+    /// names tab completion picks from, commands for the first word of a
+    /// line and arguments (cvars etc.) for the rest.
+    pub fn set_completions(&mut self, command_names: Vec<String>, argument_names: Vec<String>) {
+        self.command_names = command_names;
+        self.argument_names = argument_names;
+    }
+
+    /// number of buffer lines that fit above the input line.
+    fn visible_lines(&self) -> usize {
+        (self.console_height.saturating_sub(2 * PADDING) / self.line_height).saturating_sub(1)
+            as usize
+    }
+
+    /// top of the input line, the bottom row of the console.
+    fn input_y(&self) -> i32 {
+        self.console_height
+            .saturating_sub(PADDING + self.line_height) as i32
+    }
+
+    /// the wrapped rows that fit above the input line, oldest first.
+    fn visible_rows(&self) -> Vec<(String, Color)> {
+        let rows_wanted = self.visible_lines();
+        let width = self.inner_width();
+        let end = self.buffer.len() - self.scroll;
+        let mut rows = Vec::new();
+        for (line, color) in self.buffer.range(..end).rev() {
+            let wrapped = wrap_text(line, width, |s| self.text_width(s));
+            rows.extend(wrapped.into_iter().rev().map(|row| (row, *color)));
+            if rows.len() >= rows_wanted {
+                break;
+            }
+        }
+        rows.truncate(rows_wanted);
+        rows.reverse();
+        rows
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.buffer.len().saturating_sub(self.visible_lines())
+    }
+
+    fn scroll_by(&mut self, lines: isize) {
+        let scroll = self.scroll as isize + lines;
+        self.scroll = (scroll.max(0) as usize).min(self.max_scroll());
+    }
+
+    fn tab_complete(&mut self) {
+        let start = self.input.word_start();
+        let prefix = String::from(&self.input.before_cursor()[start..]);
+        let candidates = if self.input.text()[..start].trim().is_empty() {
+            &self.command_names
+        } else {
+            &self.argument_names
+        };
+
+        if let Some(completion) = complete(&prefix, candidates.iter().map(|c| c.as_str())) {
+            if !completion.matches.is_empty() {
+                self.print(completion.matches.join("  "));
+            }
+            self.input.replace_before_cursor(start, &completion.text);
+        }
+    }
 }
 
-impl<'ttf, 'a, 'callback> Widget for Console<'ttf, 'a, 'callback> {
+impl<'ttf, 'tc, 'callback> Widget for Console<'ttf, 'tc, 'callback> {
     fn get_current_string(&self) -> String {
-        self.current_string.clone()
+        String::from(self.input.text())
     }
 
-    fn update(&mut self, _: f32) {
-        todo!()
+    fn update(&mut self, dt: f32) {
+        self.slide.update(dt);
     }
 
-    fn update_event(&mut self, event: sdl2::event::Event) {
+    fn update_event(&mut self, event: sdl2::event::Event) -> EventResult {
         let mut handled_string = None;
+        // while open the console has the whole keyboard, only key ups and
+        // escape get through to whatever is below.
+        let mut result = EventResult::Ignored;
         match event {
+            // typed characters come through as text so the keyboard layout,
+            // shift and unicode are all handled by sdl.
+            Event::TextInput { text, .. } => {
+                self.input.insert_str(&text);
+                result = EventResult::Handled;
+            }
             Event::KeyDown {
                 keycode: Some(t),
-                repeat: false,
+                keymod,
+                repeat,
                 ..
             } => {
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                result = EventResult::Handled;
                 match t {
-                    Keycode::Space => {
-                        self.current_string += " ";
+                    Keycode::Escape => result = EventResult::Ignored,
+                    Keycode::Backspace if ctrl => self.input.delete_word_back(),
+                    Keycode::Backspace => self.input.backspace(),
+                    Keycode::Delete if ctrl => self.input.delete_word_forward(),
+                    Keycode::Delete => self.input.delete(),
+                    Keycode::W if ctrl => self.input.delete_word_back(),
+                    Keycode::Left if ctrl => self.input.word_left(),
+                    Keycode::Left => self.input.left(),
+                    Keycode::Right if ctrl => self.input.word_right(),
+                    Keycode::Right => self.input.right(),
+                    Keycode::Home => self.input.home(),
+                    Keycode::End => self.input.end(),
+                    Keycode::Up => {
+                        if let Some(line) = self.history.previous(self.input.text()) {
+                            let line = String::from(line);
+                            self.input.set_text(&line);
+                        }
                     }
-                    Keycode::Backspace => {
-                        self.current_string.pop();
+                    Keycode::Down => {
+                        if let Some(line) = self.history.next() {
+                            let line = String::from(line);
+                            self.input.set_text(&line);
+                        }
                     }
-                    Keycode::KpEnter | Keycode::Return => {
-                        self.buffer.push(self.current_string.clone());
-                        handled_string = Some(self.current_string.clone());
-                        self.current_string.clear();
+                    Keycode::PageUp => self.scroll_by(self.visible_lines() as isize),
+                    Keycode::PageDown => self.scroll_by(-(self.visible_lines() as isize)),
+                    Keycode::Tab => self.tab_complete(),
+                    Keycode::KpEnter | Keycode::Return if !repeat => {
+                        let line = self.input.take();
+                        self.history.push(&line);
+                        self.scroll = 0;
+                        self.print(line.clone());
+                        handled_string = Some(line);
                     }
                     _ => (),
                 };
-                match t as i32 {
-                    // a-z
-                    97..=122 => {
-                        self.current_string += &format!("{}", t);
-                    }
-                    _ => {
-                        // println!("hello");
-                    }
-                };
             }
 
             Event::KeyUp {
@@ -103,6 +292,16 @@ impl<'ttf, 'a, 'callback> Widget for Console<'ttf, 'a, 'callback> {
                 repeat: false,
                 ..
             } => {}
+            // the wheel scrolls back through the buffer a few lines a notch.
+            Event::MouseWheel { .. } => {
+                let steps = mouse::wheel(&event).unwrap_or(0);
+                self.scroll_by(steps as isize * WHEEL_LINES);
+                result = EventResult::Handled;
+            }
+            // clicks on the console don't reach the game underneath.
+            _ if mouse::position(&event).is_some_and(|(x, y)| self.frame.contains(x, y)) => {
+                result = EventResult::Handled;
+            }
             _ => (),
         };
 
@@ -110,88 +309,64 @@ impl<'ttf, 'a, 'callback> Widget for Console<'ttf, 'a, 'callback> {
             Some(t) => (self.enter_callback)(t),
             None => (),
         }
+        result
     }
 }
 
-impl<'ttf, 'a, 'callback> DrawableWidget for Console<'ttf, 'a, 'callback> {
-    fn draw(&mut self, canvas: &mut Canvas<Window>, _x: u32, _y: u32) {
-        let background_rec = Rect::new(0, 0, self.console_width, self.console_height);
+impl<'ttf, 'tc, 'callback> DrawableWidget for Console<'ttf, 'tc, 'callback> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        let (x, y) = (x as i32, y as i32 + self.slide.value().round() as i32);
+        let frame = Rect::new(x, y, self.console_width, self.console_height);
+        self.frame = Bounds::new(x, y, self.console_width, self.console_height);
 
-        canvas.set_draw_color(Color::RGB(34, 39, 46));
+        // the input line slides left once the caret would run off the right edge.
+        let caret_x = self.text_width(self.input.before_cursor());
+        let input_offset = caret_x.saturating_sub(self.inner_width().saturating_sub(CARET_WIDTH));
+        let input_x = x + PADDING as i32 - input_offset as i32;
+        let input_y = y + self.input_y();
+        let rows = self.visible_rows();
 
-        let texture_creator = canvas.texture_creator();
-        let mut console_texture = texture_creator
-            .create_texture(
-                None,
-                sdl2::render::TextureAccess::Target,
-                self.console_width,
-                self.console_height,
-            )
-            .unwrap();
+        // clipped to the frame so text past the edges isn't drawn.
+        canvas.set_clip_rect(frame);
+        canvas.set_draw_color(self.theme.console_background);
+        canvas.fill_rect(frame).expect("Failed to fill rect");
 
-        // performs background and buffer drawing.
-        canvas
-            .with_texture_canvas(&mut console_texture, |user_context| {
-                // draw the backbuffer.
-                user_context.set_draw_color(Color::RGBA(0, 200, 0, 255));
-                user_context
-                    .fill_rect(Rect::new(0, 0, self.console_width, self.console_height))
-                    .expect("Failed to fill rect");
-
-                for (index, i) in self.buffer.iter().enumerate() {
-                    let s = self
-                        .font
-                        .render(i)
-                        .blended(Color::RGBA(255, 0, 0, 255))
-                        .map_err(|e| e.to_string())
-                        .unwrap();
-                    let k = texture_creator.create_texture_from_surface(&s).unwrap();
-                    // let q = k.query();
-                    let target_widget = i.len() as u32 * self.p_widget;
-                    let rec = Rect::new(
-                        0,
-                        self.char_height as i32 * index as i32,
-                        target_widget,
-                        self.char_height as u32,
-                    );
-                    user_context.copy(&k, None, Some(rec)).unwrap();
-                }
-            })
-            .expect("Failed to draw console backbuffer");
-        canvas
-            .copy(&console_texture, None, Some(background_rec))
-            .unwrap();
+        let mut text = self.text.borrow_mut();
+        // newest row sits just above the input line.
+        let top = input_y - (rows.len() as u32 * self.line_height) as i32;
+        for (index, (row, color)) in rows.iter().enumerate() {
+            let row_y = top + (index as u32 * self.line_height) as i32;
+            text.draw(canvas, self.font, row, *color, x + PADDING as i32, row_y)
+                .expect("Failed to draw console line");
+        }
 
-        // draw drawing of the current string / user provided input and
+        // the current string / user provided input and
         // the prompt icon (todo) add prompt icon.
-        if self.get_current_string().len() != 0 {
-            // important that surface is member variable of
-            // class, can get segfaults on mac os x platform if not,
-            // guessing that there is some lifetime item that is being
-            // violated.
-
-            let target_widget = self.current_string.len() as u32 * self.p_widget;
-            let target_rect = sdl2::rect::Rect::new(0, 0, target_widget, 30);
-
-            self.surface = Some(
-                self.font
-                    .render(&self.get_current_string())
-                    .blended(Color::RGBA(255, 0, 0, 255))
-                    .map_err(|e| e.to_string())
-                    .unwrap(),
-            );
-
-            match self.surface {
-                Some(ref s) => {
-                    let s_texture = texture_creator
-                        .create_texture_from_surface(&s)
-                        .map_err(|e| e.to_string())
-                        .unwrap();
-                    canvas.set_draw_color(Color::RGBA(195, 217, 255, 255));
-                    canvas.copy(&s_texture, None, Some(target_rect)).unwrap();
-                }
-                None => (),
-            }
-        }
+        text.draw(
+            canvas,
+            self.font,
+            self.input.text(),
+            self.theme.console_text,
+            input_x,
+            input_y,
+        )
+        .expect("Failed to draw console input");
+
+        // caret sits just after the text left of the cursor.
+        canvas.set_draw_color(self.theme.console_text);
+        canvas
+            .fill_rect(Rect::new(
+                input_x + caret_x as i32,
+                input_y,
+                CARET_WIDTH,
+                self.line_height,
+            ))
+            .expect("Failed to draw caret");
+        canvas.set_clip_rect(None);
+    }
+
+    /// the console keeps to the top half of the window.
+    fn resize(&mut self, width: u32, height: u32) {
+        self.set_size(width, height / 2);
     }
 }
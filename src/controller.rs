@@ -0,0 +1,373 @@
+/// sources of player input, lets the same game loop be driven by a person
+/// at the keyboard or by some sort of ai.
+use std::fs;
+use std::path::Path;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use crate::asteroids::{GameInput, GameState};
+use crate::bot_protocol::{self, ExternalController};
+
+/// decides on the input for the player each tick of the game.
+pub trait Controller {
+    fn decide(&mut self, game_state: &GameState) -> GameInput;
+
+    /// raw window events, only controllers that care about the
+    /// keyboard need to do anything with these.
+    fn handle_event(&mut self, _event: &Event) {}
+}
+
+/// Builds a controller from a startup spec.
+/// keyboard, bot, replay:<file>, agent:<file> or external:<command>
+pub fn from_spec(spec: &str) -> Result<Box<dyn Controller>, String> {
+    let (kind, arg) = match spec.find(':') {
+        Some(i) => (&spec[..i], Some(&spec[i + 1..])),
+        None => (spec, None),
+    };
+
+    match (kind, arg) {
+        ("keyboard", None) => Ok(Box::new(KeyboardController::new())),
+        ("bot", None) => Ok(Box::new(ScriptedController::new())),
+        ("replay", Some(path)) => Ok(Box::new(ReplayController::from_file(Path::new(path))?)),
+        ("agent", Some(path)) => Ok(Box::new(AgentController::from_file(Path::new(path))?)),
+        ("external", Some(command)) => Ok(Box::new(ExternalController::spawn(
+            command,
+            bot_protocol::DEFAULT_TIMEOUT,
+        )?)),
+        ("replay", None) | ("agent", None) => {
+            Err(format!("'{}' needs a file, {}:<file>", kind, kind))
+        }
+        ("external", None) => Err(String::from(
+            "'external' needs a command, external:<command>",
+        )),
+        _ => Err(format!(
+            "unknown controller '{}', expected keyboard, bot, replay:<file>, agent:<file> or external:<command>",
+            spec
+        )),
+    }
+}
+
+/// arrow keys or wasd to fly, space to shoot.
+#[derive(Default)]
+pub struct KeyboardController {
+    left: bool,
+    right: bool,
+    thrusters: bool,
+    shoot: bool,
+}
+
+impl KeyboardController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Controller for KeyboardController {
+    fn decide(&mut self, _game_state: &GameState) -> GameInput {
+        let mut rotation = 0.0;
+        if self.left {
+            rotation -= 1.0;
+        }
+        if self.right {
+            rotation += 1.0;
+        }
+        GameInput {
+            rotation,
+            shoot: self.shoot,
+            thrusters: self.thrusters,
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event) {
+        let (keycode, pressed) = match event {
+            Event::KeyDown {
+                keycode: Some(k), ..
+            } => (*k, true),
+            Event::KeyUp {
+                keycode: Some(k), ..
+            } => (*k, false),
+            _ => return,
+        };
+
+        match keycode {
+            Keycode::Left | Keycode::A => self.left = pressed,
+            Keycode::Right | Keycode::D => self.right = pressed,
+            Keycode::Up | Keycode::W => self.thrusters = pressed,
+            Keycode::Space => self.shoot = pressed,
+            _ => (),
+        }
+    }
+}
+
+/// plays back a previously recorded list of inputs, one per tick.
+/// once the recording runs out the player just sits idle.
+pub struct ReplayController {
+    inputs: Vec<GameInput>,
+    index: usize,
+}
+
+impl ReplayController {
+    pub fn new(inputs: Vec<GameInput>) -> Self {
+        Self { inputs, index: 0 }
+    }
+
+    /// reads a replay file, one GameInput per line, blank lines are skipped.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read replay {}: {}", path.display(), e))?;
+        let mut inputs = Vec::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let input = line
+                .parse()
+                .map_err(|e| format!("{}:{}: {}", path.display(), line_number + 1, e))?;
+            inputs.push(input);
+        }
+        Ok(Self::new(inputs))
+    }
+
+    pub fn finished(&self) -> bool {
+        self.index >= self.inputs.len()
+    }
+}
+
+impl Controller for ReplayController {
+    fn decide(&mut self, _game_state: &GameState) -> GameInput {
+        match self.inputs.get(self.index) {
+            Some(input) => {
+                self.index += 1;
+                *input
+            }
+            None => GameInput::default(),
+        }
+    }
+}
+
+/// difference between two headings wrapped into -PI -> PI.
+fn angle_between(from: f64, to: f64) -> f64 {
+    let two_pi = 2.0 * std::f64::consts::PI;
+    let mut diff = (to - from) % two_pi;
+    if diff > std::f64::consts::PI {
+        diff -= two_pi;
+    }
+    if diff < -std::f64::consts::PI {
+        diff += two_pi;
+    }
+    diff
+}
+
+/// heading change needed to point at the closest asteroid and its distance.
+fn nearest_asteroid(game_state: &GameState) -> Option<(f64, f64)> {
+    let player = game_state.player.bounding_box();
+    game_state
+        .asteroids
+        .iter()
+        .map(|ast| {
+            let b = ast.bounding_box();
+            let dx = b.pos_x - player.pos_x;
+            let dy = b.pos_y - player.pos_y;
+            let heading = dy.atan2(dx);
+            (
+                angle_between(game_state.player.direction(), heading),
+                (dx * dx + dy * dy).sqrt(),
+            )
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// hand written bot, turns towards the nearest asteroid and shoots once lined up.
+pub struct ScriptedController {
+    /// how close in radians the heading has to be before shooting.
+    pub aim_tolerance: f64,
+}
+
+impl ScriptedController {
+    pub fn new() -> Self {
+        Self { aim_tolerance: 0.2 }
+    }
+}
+
+impl Default for ScriptedController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Controller for ScriptedController {
+    fn decide(&mut self, game_state: &GameState) -> GameInput {
+        match nearest_asteroid(game_state) {
+            Some((diff, _)) => GameInput {
+                rotation: diff.signum(),
+                shoot: diff.abs() < self.aim_tolerance,
+                thrusters: false,
+            },
+            None => GameInput::default(),
+        }
+    }
+}
+
+/// number of values AgentController::features produces.
+pub const AGENT_FEATURES: usize = 5;
+
+/// linear policy produced by training, one row of weights for each of
+/// rotation, shoot and thrusters.
+pub struct AgentController {
+    weights: [[f64; AGENT_FEATURES]; 3],
+}
+
+impl AgentController {
+    pub fn new(weights: [[f64; AGENT_FEATURES]; 3]) -> Self {
+        Self { weights }
+    }
+
+    /// weights file is three lines of AGENT_FEATURES numbers separated by whitespace.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read agent {}: {}", path.display(), e))?;
+        let rows: Vec<&str> = contents.lines().filter(|l| !l.trim().is_empty()).collect();
+        if rows.len() != 3 {
+            return Err(format!(
+                "{}: expected 3 rows of weights got {}",
+                path.display(),
+                rows.len()
+            ));
+        }
+
+        let mut weights = [[0.0; AGENT_FEATURES]; 3];
+        for (row, line) in rows.iter().enumerate() {
+            let values: Vec<f64> = line
+                .split_whitespace()
+                .map(|v| v.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("{}:{}: {}", path.display(), row + 1, e))?;
+            if values.len() != AGENT_FEATURES {
+                return Err(format!(
+                    "{}:{}: expected {} weights got {}",
+                    path.display(),
+                    row + 1,
+                    AGENT_FEATURES,
+                    values.len()
+                ));
+            }
+            weights[row].copy_from_slice(&values);
+        }
+        Ok(Self::new(weights))
+    }
+
+    /// what the agent sees of the world, the last value is the bias.
+    pub fn features(game_state: &GameState) -> [f64; AGENT_FEATURES] {
+        let count = game_state.asteroids.len() as f64;
+        match nearest_asteroid(game_state) {
+            Some((diff, dist)) => [diff.sin(), diff.cos(), 1.0 / (1.0 + dist), count, 1.0],
+            None => [0.0, 0.0, 0.0, count, 1.0],
+        }
+    }
+}
+
+impl Controller for AgentController {
+    fn decide(&mut self, game_state: &GameState) -> GameInput {
+        let features = Self::features(game_state);
+        let output: Vec<f64> = self
+            .weights
+            .iter()
+            .map(|row| row.iter().zip(features.iter()).map(|(w, f)| w * f).sum())
+            .collect();
+
+        GameInput {
+            rotation: output[0].tanh(),
+            shoot: output[1] > 0.0,
+            thrusters: output[2] > 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroids::{game_init, Asteroid};
+
+    fn single_asteroid_state(x: f64, y: f64) -> GameState {
+        let mut game_state = game_init();
+        game_state.asteroids = vec![Asteroid::new(x, y, 0.0, 0.0, 8.0)];
+        game_state
+    }
+
+    #[test]
+    fn test_input_line_round_trip() {
+        let input = GameInput {
+            rotation: -0.5,
+            shoot: true,
+            thrusters: false,
+        };
+        let parsed: GameInput = input.to_string().parse().unwrap();
+        assert_eq!(parsed, input);
+        assert!("1.0 2 0".parse::<GameInput>().is_err());
+        assert!("1.0 1".parse::<GameInput>().is_err());
+    }
+
+    #[test]
+    fn test_replay_plays_in_order_then_idles() {
+        let first = GameInput {
+            rotation: 1.0,
+            shoot: true,
+            thrusters: false,
+        };
+        let second = GameInput {
+            rotation: 0.0,
+            shoot: false,
+            thrusters: true,
+        };
+        let game_state = game_init();
+        let mut replay = ReplayController::new(vec![first, second]);
+
+        assert_eq!(replay.decide(&game_state), first);
+        assert_eq!(replay.decide(&game_state), second);
+        assert!(replay.finished());
+        assert_eq!(replay.decide(&game_state), GameInput::default());
+    }
+
+    #[test]
+    fn test_scripted_shoots_when_aimed() {
+        // player starts at 50,50 facing 0 radians, straight along x.
+        let mut bot = ScriptedController::new();
+        let input = bot.decide(&single_asteroid_state(80.0, 50.0));
+        assert!(input.shoot);
+
+        let input = bot.decide(&single_asteroid_state(50.0, 80.0));
+        assert!(!input.shoot);
+        assert_eq!(input.rotation, 1.0);
+    }
+
+    #[test]
+    fn test_agent_weights() {
+        // only the bias matters, always turn left, shoot and never thrust.
+        let mut agent = AgentController::new([
+            [0.0, 0.0, 0.0, 0.0, -10.0],
+            [0.0, 0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0, 0.0, -1.0],
+        ]);
+        let input = agent.decide(&single_asteroid_state(80.0, 50.0));
+        assert!(input.rotation < -0.99);
+        assert!(input.shoot);
+        assert!(!input.thrusters);
+    }
+
+    #[test]
+    fn test_from_spec() {
+        assert!(from_spec("keyboard").is_ok());
+        assert!(from_spec("bot").is_ok());
+        assert_eq!(
+            from_spec("replay").err().unwrap(),
+            "'replay' needs a file, replay:<file>"
+        );
+        assert_eq!(
+            from_spec("external").err().unwrap(),
+            "'external' needs a command, external:<command>"
+        );
+        assert!(from_spec("replay:does/not/exist").is_err());
+        assert!(from_spec("joystick").is_err());
+    }
+}
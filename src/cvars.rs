@@ -0,0 +1,374 @@
+/// console variables, named settings that can be changed from the console
+/// while the game runs and saved out to a config file.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::asteroids::Tuning;
+
+/// config file cvars are saved to and loaded from at startup.
+pub const CONFIG_FILE: &str = "rasteroids.cfg";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CvarValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl CvarValue {
+    /// parses text as the same kind of value as self.
+    fn parse_as(&self, text: &str) -> Result<CvarValue, String> {
+        match self {
+            CvarValue::Int(_) => text
+                .parse()
+                .map(CvarValue::Int)
+                .map_err(|_| format!("'{}' is not a whole number", text)),
+            // nan and inf parse fine but would slip past the range check.
+            CvarValue::Float(_) => text
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(CvarValue::Float)
+                .ok_or_else(|| format!("'{}' is not a number", text)),
+            CvarValue::Bool(_) => match text {
+                "1" | "true" | "on" => Ok(CvarValue::Bool(true)),
+                "0" | "false" | "off" => Ok(CvarValue::Bool(false)),
+                _ => Err(format!("'{}' is not on or off", text)),
+            },
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            CvarValue::Int(v) => *v as f64,
+            CvarValue::Float(v) => *v,
+            CvarValue::Bool(v) => *v as u8 as f64,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            CvarValue::Int(_) => "int",
+            CvarValue::Float(_) => "float",
+            CvarValue::Bool(_) => "bool",
+        }
+    }
+}
+
+impl fmt::Display for CvarValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CvarValue::Int(v) => write!(f, "{}", v),
+            CvarValue::Float(v) => write!(f, "{}", v),
+            CvarValue::Bool(v) => write!(f, "{}", if *v { "on" } else { "off" }),
+        }
+    }
+}
+
+pub struct Cvar {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub default: CvarValue,
+    /// inclusive range numbers must be in, ignored for bools.
+    pub min: f64,
+    pub max: f64,
+    value: CvarValue,
+}
+
+impl Cvar {
+    pub fn value(&self) -> CvarValue {
+        self.value
+    }
+
+    /// one line description, e.g. "thrust 2 (float 0..50, default 2) ship speed".
+    pub fn describe(&self) -> String {
+        let range = match self.default {
+            CvarValue::Bool(_) => String::new(),
+            _ => format!(" {}..{}", self.min, self.max),
+        };
+        format!(
+            "{} {} ({}{}, default {}) {}",
+            self.name,
+            self.value,
+            self.default.kind(),
+            range,
+            self.default,
+            self.help
+        )
+    }
+}
+
+pub struct CvarRegistry {
+    cvars: BTreeMap<&'static str, Cvar>,
+}
+
+impl CvarRegistry {
+    pub fn new() -> Self {
+        Self {
+            cvars: BTreeMap::new(),
+        }
+    }
+
+    /// all of the cvars the game uses, at their defaults.
+    pub fn with_game_cvars() -> Self {
+        let tuning = Tuning::default();
+        let mut cvars = Self::new();
+        cvars.register(
+            "bullet_lifetime",
+            "time a bullet lasts",
+            CvarValue::Float(tuning.bullet_life_time),
+            0.1,
+            1000.0,
+        );
+        cvars.register(
+            "fire_cooldown",
+            "ticks between shots",
+            CvarValue::Int(tuning.fire_cooldown as i64),
+            0.0,
+            1000.0,
+        );
+        cvars.register(
+            "thrust",
+            "ship speed with the thrusters on",
+            CvarValue::Float(tuning.thrust),
+            0.0,
+            50.0,
+        );
+        cvars.register(
+            "drag",
+            "fraction of ship speed lost per unit time",
+            CvarValue::Float(tuning.drag),
+            0.0,
+            100.0,
+        );
+        cvars.register(
+            "world_width",
+            "width of the wrap around world",
+            CvarValue::Float(tuning.world_width),
+            10.0,
+            10000.0,
+        );
+        cvars.register(
+            "world_height",
+            "height of the wrap around world",
+            CvarValue::Float(tuning.world_height),
+            10.0,
+            10000.0,
+        );
+        cvars.register(
+            "show_fps",
+            "show frames per second in the corner",
+            CvarValue::Bool(false),
+            0.0,
+            1.0,
+        );
+        cvars.register(
+            "console_pause",
+            "pause the game while the console is open",
+            CvarValue::Bool(true),
+            0.0,
+            1.0,
+        );
+        cvars
+    }
+
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        default: CvarValue,
+        min: f64,
+        max: f64,
+    ) {
+        self.cvars.insert(
+            name,
+            Cvar {
+                name,
+                help,
+                default,
+                min,
+                max,
+                value: default,
+            },
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cvar> {
+        self.cvars.get(name)
+    }
+
+    /// registered cvar names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.cvars.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cvar> {
+        self.cvars.values()
+    }
+
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        self.get(name).map(|c| c.value.as_f64())
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)?.value {
+            CvarValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// sets a cvar from text, checking it's the right type and in range.
+    pub fn set(&mut self, name: &str, text: &str) -> Result<(), String> {
+        let cvar = self
+            .cvars
+            .get_mut(name)
+            .ok_or_else(|| format!("unknown cvar '{}'", name))?;
+        let value = cvar.default.parse_as(text)?;
+        let number = value.as_f64();
+        if !matches!(value, CvarValue::Bool(_)) && (number < cvar.min || number > cvar.max) {
+            return Err(format!(
+                "{} must be between {} and {}",
+                name, cvar.min, cvar.max
+            ));
+        }
+        cvar.value = value;
+        Ok(())
+    }
+
+    pub fn reset(&mut self, name: &str) -> Result<(), String> {
+        let cvar = self
+            .cvars
+            .get_mut(name)
+            .ok_or_else(|| format!("unknown cvar '{}'", name))?;
+        cvar.value = cvar.default;
+        Ok(())
+    }
+
+    /// copies the gameplay cvars into the game's tuning.
+    pub fn apply(&self, tuning: &mut Tuning) {
+        let get = |name: &str, fallback: f64| self.get_f64(name).unwrap_or(fallback);
+        tuning.bullet_life_time = get("bullet_lifetime", tuning.bullet_life_time);
+        tuning.fire_cooldown = get("fire_cooldown", tuning.fire_cooldown as f64) as i16;
+        tuning.thrust = get("thrust", tuning.thrust);
+        tuning.drag = get("drag", tuning.drag);
+        tuning.world_width = get("world_width", tuning.world_width);
+        tuning.world_height = get("world_height", tuning.world_height);
+    }
+
+    /// config file contents, one "name value" per line.
+    pub fn to_config(&self) -> String {
+        let mut out = String::from("# rasteroids cvars, name value\n");
+        for cvar in self.cvars.values() {
+            out += &format!("{} {}\n", cvar.name, cvar.value);
+        }
+        out
+    }
+
+    /// applies config file contents. bad lines are skipped and returned as
+    /// errors so one typo doesn't throw away the rest of the file.
+    pub fn load_config(&mut self, contents: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, char::is_whitespace);
+            let name = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("").trim();
+            if let Err(e) = self.set(name, value) {
+                errors.push(format!("line {}: {}", line_number + 1, e));
+            }
+        }
+        errors
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_config())
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+
+    /// loads a config file, Err if it couldn't be read at all.
+    pub fn load(&mut self, path: &Path) -> Result<Vec<String>, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Ok(self.load_config(&contents))
+    }
+}
+
+impl Default for CvarRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_checks_type_and_range() {
+        let mut cvars = CvarRegistry::with_game_cvars();
+        assert!(cvars.set("thrust", "4.5").is_ok());
+        assert_eq!(cvars.get_f64("thrust"), Some(4.5));
+
+        assert!(cvars.set("thrust", "fast").is_err());
+        assert!(cvars.set("thrust", "51").is_err());
+        assert!(cvars.set("fire_cooldown", "2.5").is_err());
+        assert!(cvars.set("nope", "1").is_err());
+        assert_eq!(cvars.get_f64("thrust"), Some(4.5));
+
+        assert_eq!(
+            cvars.set("world_width", "nan"),
+            Err(String::from("'nan' is not a number"))
+        );
+        assert!(cvars.set("world_width", "inf").is_err());
+        assert!(cvars.set("thrust", "-inf").is_err());
+        assert_eq!(cvars.get_f64("world_width"), Some(Tuning::default().world_width));
+
+        assert!(cvars.set("console_pause", "off").is_ok());
+        assert_eq!(cvars.get_bool("console_pause"), Some(false));
+
+        cvars.reset("thrust").unwrap();
+        assert_eq!(cvars.get_f64("thrust"), Some(Tuning::default().thrust));
+    }
+
+    #[test]
+    fn test_apply_to_tuning() {
+        let mut cvars = CvarRegistry::with_game_cvars();
+        let mut tuning = Tuning::default();
+        cvars.apply(&mut tuning);
+        assert_eq!(tuning, Tuning::default());
+
+        cvars.set("fire_cooldown", "5").unwrap();
+        cvars.set("world_width", "200").unwrap();
+        cvars.apply(&mut tuning);
+        assert_eq!(tuning.fire_cooldown, 5);
+        assert_eq!(tuning.world_width, 200.0);
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let mut cvars = CvarRegistry::with_game_cvars();
+        cvars.set("drag", "0.5").unwrap();
+        cvars.set("console_pause", "off").unwrap();
+        let config = cvars.to_config();
+
+        let mut loaded = CvarRegistry::with_game_cvars();
+        assert!(loaded.load_config(&config).is_empty());
+        assert_eq!(loaded.get_f64("drag"), Some(0.5));
+        assert_eq!(loaded.get_bool("console_pause"), Some(false));
+    }
+
+    #[test]
+    fn test_config_bad_lines_skipped() {
+        let mut cvars = CvarRegistry::with_game_cvars();
+        let errors = cvars.load_config("thrust 3\nbogus 1\n\n# comment\ndrag lots\n");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("line 2:"));
+        assert!(errors[1].starts_with("line 5:"));
+        assert_eq!(cvars.get_f64("thrust"), Some(3.0));
+    }
+}
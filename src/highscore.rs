@@ -0,0 +1,303 @@
+/// the best scores ever made, kept in a plain text file next to the game.
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::asteroids::GameState;
+
+pub const HIGHSCORE_FILE: &str = "highscores.txt";
+/// how many scores the table keeps.
+pub const MAX_ENTRIES: usize = 10;
+/// names are cut down to this many characters.
+pub const MAX_NAME_LEN: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u64,
+    pub wave: u32,
+    pub seed: u64,
+    /// day the score was made, yyyy-mm-dd.
+    pub date: String,
+}
+
+impl HighScore {
+    /// a score for the game that just finished, made today.
+    pub fn from_game(game_state: &GameState, name: &str) -> Self {
+        Self {
+            name: clean_name(name),
+            score: game_state.score,
+            wave: game_state.wave,
+            seed: game_state.seed,
+            date: today(),
+        }
+    }
+
+    /// file line, tab separated with the name last as it can hold spaces.
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.score, self.wave, self.seed, self.date, self.name
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = line.splitn(5, '\t').collect();
+        if parts.len() != 5 {
+            return Err(String::from("expected score, wave, seed, date and name"));
+        }
+        Ok(Self {
+            score: parse_field(parts[0], "score")?,
+            wave: parse_field(parts[1], "wave")?,
+            seed: parse_field(parts[2], "seed")?,
+            date: String::from(parts[3]),
+            name: clean_name(parts[4]),
+        })
+    }
+}
+
+/// a number that has to fit its field, anything else marks the line bad.
+fn parse_field<T: FromStr>(text: &str, what: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("bad {} '{}'", what, text))
+}
+
+/// trims, drops tabs and control characters, caps the length and falls
+/// back to a placeholder so every score has some name.
+pub fn clean_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LEN)
+        .collect();
+    let name = name.trim();
+    if name.is_empty() {
+        String::from("anonymous")
+    } else {
+        String::from(name)
+    }
+}
+
+/// today's date in utc as yyyy-mm-dd.
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// year, month and day of a count of days since 1970-01-01, from
+/// howard hinnant's date algorithms.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// highest score first, never more than MAX_ENTRIES long.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScoreTable {
+    entries: Vec<HighScore>,
+}
+
+impl HighScoreTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// true if score would make it onto the table.
+    pub fn qualifies(&self, score: u64) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_none_or(|e| score > e.score))
+    }
+
+    /// adds a score, returning its rank from 1 or None if it didn't make
+    /// the table. ties go below the scores already there.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(index + 1)
+    }
+
+    /// one line per score for showing on screen.
+    pub fn lines(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return vec![String::from("no high scores yet")];
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                format!(
+                    "{}. {} {} wave {} {}",
+                    i + 1,
+                    e.name,
+                    e.score,
+                    e.wave,
+                    e.date
+                )
+            })
+            .collect()
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let mut out = String::from("# rasteroids high scores: score wave seed date name\n");
+        for entry in self.entries.iter() {
+            out += &entry.to_line();
+            out.push('\n');
+        }
+        out
+    }
+
+    /// reads a table back in. lines that don't parse are skipped and
+    /// returned as errors, so a damaged file loses as little as possible.
+    pub fn from_file_contents(contents: &str) -> (Self, Vec<String>) {
+        let mut table = Self::new();
+        let mut errors = Vec::new();
+        let mut scores = Vec::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match HighScore::from_line(line) {
+                Ok(entry) => scores.push(entry),
+                Err(e) => errors.push(format!("line {}: {}", line_number + 1, e)),
+            }
+        }
+        // don't trust the file to be in order or the right length.
+        scores.sort_by_key(|e| Reverse(e.score));
+        for entry in scores {
+            table.insert(entry);
+        }
+        (table, errors)
+    }
+
+    /// a missing file is just an empty table, anything unreadable is
+    /// reported but still leaves an empty table to carry on with. a damaged
+    /// file is copied to a .bak first, as the next save writes over it.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                let (table, mut errors) = Self::from_file_contents(&contents);
+                if !errors.is_empty() {
+                    let backup = path.with_extension("bak");
+                    match fs::write(&backup, contents) {
+                        Ok(()) => errors.push(format!("kept a copy in {}", backup.display())),
+                        Err(e) => {
+                            errors.push(format!("failed to write {}: {}", backup.display(), e))
+                        }
+                    }
+                }
+                (table, errors)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (Self::new(), Vec::new()),
+            Err(e) => (
+                Self::new(),
+                vec![format!("failed to read {}: {}", path.display(), e)],
+            ),
+        }
+    }
+
+    /// writes to a temporary file first and moves it into place, so a crash
+    /// part way through can't leave half a table behind.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, self.to_file_contents())
+            .and_then(|_| fs::rename(&temp, path))
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(name: &str, score: u64) -> HighScore {
+        HighScore {
+            name: String::from(name),
+            score,
+            wave: 2,
+            seed: 7,
+            date: String::from("2024-02-29"),
+        }
+    }
+
+    #[test]
+    fn test_insert_keeps_order_and_length() {
+        let mut table = HighScoreTable::new();
+        assert!(!table.qualifies(0));
+        assert_eq!(table.insert(score("a", 300)), Some(1));
+        assert_eq!(table.insert(score("b", 500)), Some(1));
+        assert_eq!(table.insert(score("c", 300)), Some(3));
+        assert_eq!(table.entries()[1].name, "a");
+
+        for i in 0..MAX_ENTRIES as u64 {
+            table.insert(score("filler", 1000 + i));
+        }
+        assert_eq!(table.entries().len(), MAX_ENTRIES);
+        assert!(!table.qualifies(1000));
+        assert_eq!(table.insert(score("low", 200)), None);
+        assert_eq!(table.entries()[0].score, 1009);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let mut table = HighScoreTable::new();
+        table.insert(score("ace pilot", 900));
+        table.insert(score("rookie", 100));
+        let (loaded, errors) = HighScoreTable::from_file_contents(&table.to_file_contents());
+        assert!(errors.is_empty());
+        assert_eq!(loaded, table);
+    }
+
+    #[test]
+    fn test_corrupt_lines_skipped() {
+        let contents = "# header\n100\t1\t5\t2024-01-01\tzed\ngarbage\n\
+                        lots\t1\t5\t2024-01-01\tbad\n900\t3\t5\t2024-01-02\tace\n\
+                        50\t4294967296\t5\t2024-01-02\thuge\n";
+        let (table, errors) = HighScoreTable::from_file_contents(contents);
+        assert_eq!(errors.len(), 3);
+        assert!(errors[2].contains("bad wave '4294967296'"));
+        assert!(errors[0].starts_with("line 3:"));
+        let names: Vec<&str> = table.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["ace", "zed"]);
+    }
+
+    #[test]
+    fn test_clean_name() {
+        assert_eq!(clean_name("  sam\t "), "sam");
+        assert_eq!(clean_name(""), "anonymous");
+        assert_eq!(clean_name("abcdefghijklmnopqrstuvwxyz").len(), MAX_NAME_LEN);
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(today().len(), 10);
+    }
+}
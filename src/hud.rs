@@ -0,0 +1,178 @@
+use sdl2::event::Event;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::asteroids::GameState;
+use crate::layout::{Anchor, Bounds, Padding};
+use crate::text_cache::{FontId, TextCache};
+use crate::theme::Theme;
+use crate::widget::{DrawableWidget, EventResult, Widget};
+
+/// gap in pixels between the hud text and the edges of the window.
+const MARGIN: u32 = 10;
+/// frames the fps counter averages over.
+const FPS_SAMPLES: usize = 60;
+
+/// frames per second averaged over the last few frames, so the number
+/// shown doesn't flicker.
+#[derive(Clone, Debug, Default)]
+pub struct FpsCounter {
+    frame_times: VecDeque<f32>,
+    total: f32,
+}
+
+impl FpsCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds the time in seconds the last frame took.
+    pub fn record(&mut self, dt: f32) {
+        self.frame_times.push_back(dt);
+        self.total += dt;
+        if self.frame_times.len() > FPS_SAMPLES {
+            self.total -= self.frame_times.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.total <= 0.0 {
+            0.0
+        } else {
+            self.frame_times.len() as f32 / self.total
+        }
+    }
+
+    /// average frame time in milliseconds.
+    pub fn frame_ms(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            0.0
+        } else {
+            self.total * 1000.0 / self.frame_times.len() as f32
+        }
+    }
+}
+
+/// score, lives and wave drawn over the game, plus an optional fps counter.
+pub struct Hud<'ttf, 'tc> {
+    score: u64,
+    lives: u32,
+    wave: u32,
+    pub show_fps: bool,
+    fps: FpsCounter,
+
+    text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+    font: FontId,
+    theme: Theme,
+    width: u32,
+    height: u32,
+}
+
+impl<'ttf, 'tc> Hud<'ttf, 'tc> {
+    pub fn new(
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+    ) -> Self {
+        let font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.hud_size)
+            .unwrap();
+        Self {
+            score: 0,
+            lives: 0,
+            wave: 0,
+            show_fps: false,
+            fps: FpsCounter::new(),
+            text,
+            font,
+            theme: theme.clone(),
+            width: size.0,
+            height: size.1,
+        }
+    }
+
+    /// copies out what the hud shows, call once a frame.
+    pub fn set_game_state(&mut self, game_state: &GameState) {
+        self.score = game_state.score;
+        self.lives = game_state.lives;
+        self.wave = game_state.wave;
+    }
+}
+
+impl<'ttf, 'tc> Widget for Hud<'ttf, 'tc> {
+    fn update_event(&mut self, _: Event) -> EventResult {
+        EventResult::Ignored
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.fps.record(dt);
+    }
+
+    fn get_current_string(&self) -> String {
+        format!(
+            "score {} lives {} wave {}",
+            self.score, self.lives, self.wave
+        )
+    }
+}
+
+impl<'ttf, 'tc> DrawableWidget for Hud<'ttf, 'tc> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        let screen = Bounds::new(x as i32, y as i32, self.width, self.height);
+        let area = screen.inset(Padding::all(MARGIN));
+        let mut text = self.text.borrow_mut();
+        let line_height = text.line_height(self.font);
+
+        // score on the left, wave in the middle and lives on the right.
+        let mut labels = vec![
+            (format!("Score {}", self.score), Anchor::TopLeft),
+            (format!("Wave {}", self.wave), Anchor::Top),
+            (format!("Lives {}", self.lives), Anchor::TopRight),
+        ];
+        if self.show_fps {
+            let fps = format!("{:.0} fps {:.1} ms", self.fps.fps(), self.fps.frame_ms());
+            labels.push((fps, Anchor::BottomLeft));
+        }
+        for (label, anchor) in labels.iter() {
+            let at = area.anchored((text.text_width(self.font, label), line_height), *anchor);
+            text.draw(canvas, self.font, label, self.theme.text, at.x, at.y)
+                .expect("Failed to draw hud");
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fps_average() {
+        let mut fps = FpsCounter::new();
+        assert_eq!(fps.fps(), 0.0);
+        assert_eq!(fps.frame_ms(), 0.0);
+        for _ in 0..10 {
+            fps.record(0.02);
+        }
+        assert!((fps.fps() - 50.0).abs() < 0.01);
+        assert!((fps.frame_ms() - 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fps_forgets_old_frames() {
+        let mut fps = FpsCounter::new();
+        fps.record(1.0);
+        for _ in 0..FPS_SAMPLES {
+            fps.record(0.01);
+        }
+        assert!((fps.fps() - 100.0).abs() < 0.1);
+    }
+}
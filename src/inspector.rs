@@ -0,0 +1,450 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::asteroids::{EntityField, EntityId, GameState, RENDER_SIZE};
+use crate::layout::{Anchor, Bounds, Padding};
+use crate::line_editor::LineEditor;
+use crate::mouse;
+use crate::text_cache::{FontId, TextCache};
+use crate::theme::Theme;
+use crate::widget::{DrawableWidget, EventResult, Widget};
+
+/// widest the panel gets, it never takes more than half the window.
+const PANEL_WIDTH: u32 = 360;
+const PADDING: u32 = 8;
+/// entities the selection jumps by on page up and down.
+const PAGE: usize = 10;
+/// gap in pixels between an entity and the box highlighting it.
+const HIGHLIGHT_MARGIN: i32 = 3;
+
+/// what the inspector shows of one entity, copied out of the game each frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitySnapshot {
+    pub id: EntityId,
+    /// one value per EntityField::ALL, None where the entity has no such field.
+    pub values: Vec<Option<f64>>,
+}
+
+impl EntitySnapshot {
+    pub fn value(&self, field: EntityField) -> Option<f64> {
+        let index = EntityField::ALL.iter().position(|f| *f == field)?;
+        self.values[index]
+    }
+}
+
+/// every entity in the game, player first.
+pub fn snapshot(game_state: &GameState) -> Vec<EntitySnapshot> {
+    game_state
+        .entity_ids()
+        .into_iter()
+        .map(|id| EntitySnapshot {
+            id,
+            values: EntityField::ALL
+                .iter()
+                .map(|field| game_state.entity_field(id, *field))
+                .collect(),
+        })
+        .collect()
+}
+
+/// a change made in the inspector, applied to the game by whoever owns it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EntityEdit {
+    pub id: EntityId,
+    pub field: EntityField,
+    pub value: f64,
+}
+
+fn format_value(value: Option<f64>) -> String {
+    value.map_or_else(|| String::from("-"), |v| format!("{:.2}", v))
+}
+
+/// debug panel down the right of the window listing every entity. up and
+/// down pick one, which is boxed in the world, left and right pick a field
+/// and enter edits it.
+pub struct Inspector<'ttf, 'tc, 'callback> {
+    entities: Vec<EntitySnapshot>,
+    selected: usize,
+    /// index into EntityField::ALL.
+    field: usize,
+    /// the value being typed while a field is edited.
+    editing: Option<LineEditor>,
+    /// last error, e.g. a value that didn't parse.
+    status: Option<String>,
+
+    text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+    font: FontId,
+    theme: Theme,
+    width: u32,
+    height: u32,
+    /// where each row of the entity list was drawn, for clicking on.
+    list_rows: Vec<(Bounds, usize)>,
+    /// where the panel was drawn, mouse events inside it stop here.
+    panel: Bounds,
+
+    edit_callback: &'callback dyn Fn(EntityEdit),
+}
+
+impl<'ttf, 'tc, 'callback> Inspector<'ttf, 'tc, 'callback> {
+    pub fn new(
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        edit_callback: &'callback dyn Fn(EntityEdit),
+    ) -> Self {
+        let font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.small_size)
+            .unwrap();
+        Self {
+            entities: Vec::new(),
+            selected: 0,
+            field: 0,
+            editing: None,
+            status: None,
+            text,
+            font,
+            theme: theme.clone(),
+            width: size.0,
+            height: size.1,
+            list_rows: Vec::new(),
+            panel: Bounds::default(),
+            edit_callback,
+        }
+    }
+
+    /// copies out the entities to show, call once a frame while open.
+    pub fn set_game_state(&mut self, game_state: &GameState) {
+        self.entities = snapshot(game_state);
+        self.selected = self.selected.min(self.entities.len().saturating_sub(1));
+    }
+
+    /// shows a message under the fields, e.g. why an edit was refused.
+    pub fn set_status<S: Into<String>>(&mut self, status: S) {
+        self.status = Some(status.into());
+    }
+
+    fn selected_entity(&self) -> Option<&EntitySnapshot> {
+        self.entities.get(self.selected)
+    }
+
+    fn select(&mut self, index: isize) {
+        let last = self.entities.len().saturating_sub(1) as isize;
+        self.selected = index.clamp(0, last) as usize;
+    }
+
+    fn field(&self) -> EntityField {
+        EntityField::ALL[self.field]
+    }
+
+    fn start_editing(&mut self) {
+        let value = match self.selected_entity() {
+            Some(entity) => entity.value(self.field()),
+            None => return,
+        };
+        match value {
+            Some(value) => {
+                let mut input = LineEditor::new();
+                input.set_text(&format!("{:.2}", value));
+                self.editing = Some(input);
+                self.status = None;
+            }
+            None => self.set_status(format!("no {} to edit", self.field().name())),
+        }
+    }
+
+    fn finish_editing(&mut self) {
+        let input = match self.editing.take() {
+            Some(input) => input,
+            None => return,
+        };
+        let id = match self.selected_entity() {
+            Some(entity) => entity.id,
+            None => return,
+        };
+        match input.text().trim().parse::<f64>() {
+            Ok(value) => (self.edit_callback)(EntityEdit {
+                id,
+                field: self.field(),
+                value,
+            }),
+            Err(_) => self.set_status(format!("'{}' is not a number", input.text())),
+        }
+    }
+
+    fn edit_key(&mut self, key: Keycode, repeat: bool) {
+        let input = match self.editing.as_mut() {
+            Some(input) => input,
+            None => return,
+        };
+        match key {
+            Keycode::Backspace => input.backspace(),
+            Keycode::Delete => input.delete(),
+            Keycode::Left => input.left(),
+            Keycode::Right => input.right(),
+            Keycode::Home => input.home(),
+            Keycode::End => input.end(),
+            Keycode::Escape => self.editing = None,
+            Keycode::Return | Keycode::KpEnter if !repeat => self.finish_editing(),
+            _ => (),
+        }
+    }
+
+    fn browse_key(&mut self, key: Keycode, repeat: bool) {
+        let fields = EntityField::ALL.len();
+        let selected = self.selected as isize;
+        match key {
+            Keycode::Up => self.select(selected - 1),
+            Keycode::Down => self.select(selected + 1),
+            Keycode::PageUp => self.select(selected - PAGE as isize),
+            Keycode::PageDown => self.select(selected + PAGE as isize),
+            Keycode::Home => self.select(0),
+            Keycode::End => self.select(isize::MAX),
+            Keycode::Left => self.field = (self.field + fields - 1) % fields,
+            Keycode::Right | Keycode::Tab => self.field = (self.field + 1) % fields,
+            Keycode::Return | Keycode::KpEnter if !repeat => self.start_editing(),
+            _ => (),
+        }
+    }
+
+    /// screen box around an entity, from where the world is stretched over
+    /// the window.
+    fn highlight(&self, entity: &EntitySnapshot, screen: Bounds) -> Option<Bounds> {
+        let x = entity.value(EntityField::PosX)?;
+        let y = entity.value(EntityField::PosY)?;
+        let radius = entity.value(EntityField::Radius)?;
+        let scale_x = screen.width as f64 / RENDER_SIZE as f64;
+        let scale_y = screen.height as f64 / RENDER_SIZE as f64;
+        Some(Bounds::new(
+            screen.x + (x * scale_x) as i32 - HIGHLIGHT_MARGIN,
+            screen.y + (y * scale_y) as i32 - HIGHLIGHT_MARGIN,
+            (radius * scale_x) as u32 + 2 * HIGHLIGHT_MARGIN as u32,
+            (radius * scale_y) as u32 + 2 * HIGHLIGHT_MARGIN as u32,
+        ))
+    }
+}
+
+impl<'ttf, 'tc, 'callback> Widget for Inspector<'ttf, 'tc, 'callback> {
+    fn update_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::TextInput { text, .. } => {
+                if let Some(input) = self.editing.as_mut() {
+                    input.insert_str(&text);
+                }
+                EventResult::Handled
+            }
+            // escape backs out of an edit, or closes the inspector if there
+            // isn't one.
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } if self.editing.is_none() => EventResult::Ignored,
+            Event::KeyDown {
+                keycode: Some(k),
+                repeat,
+                ..
+            } => {
+                if self.editing.is_some() {
+                    self.edit_key(k, repeat);
+                } else {
+                    self.browse_key(k, repeat);
+                }
+                EventResult::Handled
+            }
+            Event::MouseWheel { .. } if self.editing.is_none() => {
+                let steps = mouse::wheel(&event).unwrap_or(0) as isize;
+                self.select(self.selected as isize - steps);
+                EventResult::Handled
+            }
+            Event::MouseButtonDown { x, y, .. } if self.panel.contains(x, y) => {
+                let clicked = self.list_rows.iter().find(|(row, _)| row.contains(x, y));
+                if let Some((_, index)) = clicked {
+                    self.selected = *index;
+                    self.editing = None;
+                }
+                EventResult::Handled
+            }
+            _ if mouse::position(&event).is_some_and(|(x, y)| self.panel.contains(x, y)) => {
+                EventResult::Handled
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, _: f32) {}
+
+    fn get_current_string(&self) -> String {
+        self.selected_entity()
+            .map_or_else(String::new, |e| e.id.to_string())
+    }
+}
+
+impl<'ttf, 'tc, 'callback> DrawableWidget for Inspector<'ttf, 'tc, 'callback> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        let screen = Bounds::new(x as i32, y as i32, self.width, self.height);
+
+        if let Some(entity) = self.selected_entity() {
+            if let Some(highlight) = self.highlight(entity, screen) {
+                canvas.set_draw_color(self.theme.highlight);
+                canvas
+                    .draw_rect(highlight.to_rect())
+                    .expect("Failed to draw highlight");
+            }
+        }
+
+        let panel_width = PANEL_WIDTH.min(self.width / 2);
+        self.panel = screen.anchored((panel_width, self.height), Anchor::Right);
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(self.theme.panel);
+        canvas
+            .fill_rect(self.panel.to_rect())
+            .expect("Failed to draw inspector");
+        canvas.set_blend_mode(BlendMode::None);
+
+        let mut text = self.text.borrow_mut();
+        let line_height = text.line_height(self.font);
+        let inner = self.panel.inset(Padding::all(PADDING));
+
+        // the selected entity's fields, then the list of every entity.
+        let mut lines = vec![(
+            format!("{} entities, esc to close", self.entities.len()),
+            self.theme.muted,
+        )];
+        if let Some(entity) = self.selected_entity() {
+            lines.push((entity.id.to_string(), self.theme.highlight));
+            for (index, field) in EntityField::ALL.iter().enumerate() {
+                let line = match (&self.editing, index == self.field) {
+                    (Some(input), true) => format!("> {} = {}_", field.name(), input.text()),
+                    (None, true) => {
+                        format!("> {} {}", field.name(), format_value(entity.value(*field)))
+                    }
+                    _ => format!("  {} {}", field.name(), format_value(entity.value(*field))),
+                };
+                let color = if index == self.field {
+                    self.theme.highlight
+                } else {
+                    self.theme.muted
+                };
+                lines.push((line, color));
+            }
+        }
+        match &self.status {
+            Some(status) => lines.push((status.clone(), self.theme.error)),
+            None => lines.push((String::new(), self.theme.muted)),
+        }
+        lines.push((String::new(), self.theme.muted));
+
+        let rows = inner.vstack(&vec![line_height; lines.len()], 0);
+        for ((line, color), row) in lines.iter().zip(rows.iter()) {
+            if !line.is_empty() {
+                text.draw(canvas, self.font, line, *color, row.x, row.y)
+                    .expect("Failed to draw inspector line");
+            }
+        }
+
+        // the list scrolls to keep the selected entity in view.
+        let list_top = rows.last().map_or(inner.y, |r| r.bottom());
+        let list = Bounds::new(
+            inner.x,
+            list_top,
+            inner.width,
+            (inner.bottom() - list_top).max(0) as u32,
+        );
+        let visible = (list.height / line_height.max(1)) as usize;
+        let first = (self.selected + 1).saturating_sub(visible);
+        self.list_rows.clear();
+        let shown = self.entities.iter().enumerate().skip(first).take(visible);
+        for ((index, entity), row) in shown.zip(list.vstack(&vec![line_height; visible], 0)) {
+            let line = format!(
+                "{} ({}, {})",
+                entity.id,
+                format_value(entity.value(EntityField::PosX)),
+                format_value(entity.value(EntityField::PosY))
+            );
+            let color = if index == self.selected {
+                self.theme.highlight
+            } else {
+                self.theme.muted
+            };
+            text.draw(canvas, self.font, &line, color, row.x, row.y)
+                .expect("Failed to draw inspector entity");
+            self.list_rows.push((row, index));
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroids::{game_init_seeded, game_update, GameInput};
+
+    #[test]
+    fn test_snapshot_lists_everything() {
+        let mut game_state = game_init_seeded(2);
+        let shoot = GameInput {
+            shoot: true,
+            ..GameInput::default()
+        };
+        // keep firing until a bullet survives its first tick.
+        while game_state.bullets().is_empty() {
+            game_update(&mut game_state, 0.1, &shoot);
+        }
+        let entities = snapshot(&game_state);
+        assert_eq!(
+            entities.len(),
+            1 + game_state.asteroids.len() + game_state.bullets().len()
+        );
+        assert_eq!(entities[0].id, EntityId::Player);
+        assert_eq!(entities[0].value(EntityField::LifeTime), None);
+        let bullet = entities.last().unwrap();
+        assert!(matches!(bullet.id, EntityId::Bullet(_)));
+        assert!(bullet.value(EntityField::LifeTime).is_some());
+        assert_eq!(
+            entities[1].value(EntityField::PosX),
+            Some(game_state.asteroids[0].movement().pos_x)
+        );
+    }
+
+    #[test]
+    fn test_set_entity_field() {
+        let mut game_state = game_init_seeded(2);
+        let asteroid = EntityId::Asteroid(0);
+        game_state
+            .set_entity_field(asteroid, EntityField::PosX, 12.5)
+            .unwrap();
+        assert_eq!(game_state.asteroids[0].movement().pos_x, 12.5);
+        game_state
+            .set_entity_field(EntityId::Player, EntityField::Direction, -1.0)
+            .unwrap();
+        let direction = game_state.player.direction();
+        assert!(direction > 0.0 && direction < 2.0 * std::f64::consts::PI);
+
+        assert!(game_state
+            .set_entity_field(asteroid, EntityField::Radius, 0.0)
+            .is_err());
+        assert!(game_state
+            .set_entity_field(asteroid, EntityField::LifeTime, 5.0)
+            .is_err());
+        assert!(game_state
+            .set_entity_field(EntityId::Asteroid(999), EntityField::PosX, 1.0)
+            .is_err());
+        assert!(game_state
+            .set_entity_field(asteroid, EntityField::PosY, f64::NAN)
+            .is_err());
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(Some(1.0 / 3.0)), "0.33");
+        assert_eq!(format_value(None), "-");
+    }
+}
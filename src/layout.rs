@@ -0,0 +1,233 @@
+/// placing widgets and their parts inside the window, worked out from the
+/// window size each time so everything follows it when it changes.
+use sdl2::rect::Rect;
+
+use crate::utils::spaced_internals;
+
+/// a box on screen in pixels, x and y are the top left corner.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// where in its parent a child box sits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Centre,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// space kept clear inside each edge of a box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Padding {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Padding {
+    pub fn all(amount: u32) -> Self {
+        Self {
+            top: amount,
+            right: amount,
+            bottom: amount,
+            left: amount,
+        }
+    }
+}
+
+impl Bounds {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// the whole of a window or widget of this size.
+    pub fn from_size(size: (u32, u32)) -> Self {
+        Self::new(0, 0, size.0, size.1)
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// the box with padding taken off each side, never smaller than nothing.
+    pub fn inset(&self, padding: Padding) -> Self {
+        Self::new(
+            self.x + padding.left as i32,
+            self.y + padding.top as i32,
+            self.width.saturating_sub(padding.left + padding.right),
+            self.height.saturating_sub(padding.top + padding.bottom),
+        )
+    }
+
+    /// a child of size placed at anchor inside this box. a child bigger than
+    /// the box hangs over its edges evenly rather than being squashed.
+    pub fn anchored(&self, size: (u32, u32), anchor: Anchor) -> Self {
+        let spare_x = self.width as i32 - size.0 as i32;
+        let spare_y = self.height as i32 - size.1 as i32;
+        let (along_x, along_y) = match anchor {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Centre => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        };
+        Self::new(
+            self.x + spare_x * along_x / 2,
+            self.y + spare_y * along_y / 2,
+            size.0,
+            size.1,
+        )
+    }
+
+    /// rows of the given heights top down from the top of this box, spacing
+    /// apart and as wide as it.
+    pub fn vstack(&self, heights: &[u32], spacing: u32) -> Vec<Self> {
+        let mut y = self.y;
+        heights
+            .iter()
+            .map(|height| {
+                let row = Self::new(self.x, y, self.width, *height);
+                y += (height + spacing) as i32;
+                row
+            })
+            .collect()
+    }
+
+    /// columns of the given widths left to right, spacing apart and as tall
+    /// as this box.
+    pub fn hstack(&self, widths: &[u32], spacing: u32) -> Vec<Self> {
+        let mut x = self.x;
+        widths
+            .iter()
+            .map(|width| {
+                let column = Self::new(x, self.y, *width, self.height);
+                x += (width + spacing) as i32;
+                column
+            })
+            .collect()
+    }
+
+    /// splits the box into equal cells, row by row from the top left. any
+    /// pixels that don't divide evenly are left over at the right and bottom.
+    pub fn grid(&self, columns: u32, rows: u32) -> Vec<Self> {
+        if columns == 0 || rows == 0 {
+            return Vec::new();
+        }
+        let xs = spaced_internals(self.width / columns, columns);
+        let ys = spaced_internals(self.height / rows, rows);
+        ys.iter()
+            .flat_map(|(top, bottom)| {
+                xs.iter().map(move |(left, right)| {
+                    Self::new(
+                        self.x + *left as i32,
+                        self.y + *top as i32,
+                        right - left,
+                        bottom - top,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_rect(self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
+}
+
+/// total height of rows stacked spacing apart.
+pub fn stack_height(heights: &[u32], spacing: u32) -> u32 {
+    let gaps = heights.len().saturating_sub(1) as u32;
+    heights.iter().sum::<u32>() + gaps * spacing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchored() {
+        let window = Bounds::from_size((800, 600));
+        assert_eq!(
+            window.anchored((100, 50), Anchor::TopLeft),
+            Bounds::new(0, 0, 100, 50)
+        );
+        assert_eq!(
+            window.anchored((100, 50), Anchor::Centre),
+            Bounds::new(350, 275, 100, 50)
+        );
+        assert_eq!(
+            window.anchored((100, 50), Anchor::BottomRight),
+            Bounds::new(700, 550, 100, 50)
+        );
+        let padded = window.inset(Padding::all(10));
+        assert_eq!(
+            padded.anchored((100, 50), Anchor::Right),
+            Bounds::new(690, 275, 100, 50)
+        );
+        // too big to fit overhangs both sides.
+        assert_eq!(
+            Bounds::from_size((10, 10)).anchored((20, 10), Anchor::Top),
+            Bounds::new(-5, 0, 20, 10)
+        );
+    }
+
+    #[test]
+    fn test_inset_never_negative() {
+        let b = Bounds::new(5, 5, 10, 10).inset(Padding::all(8));
+        assert_eq!(b, Bounds::new(13, 13, 0, 0));
+        assert!(!b.contains(13, 13));
+        assert!(Bounds::new(5, 5, 10, 10).contains(14, 5));
+        assert!(!Bounds::new(5, 5, 10, 10).contains(15, 5));
+    }
+
+    #[test]
+    fn test_stacks() {
+        let b = Bounds::new(10, 20, 100, 200);
+        let rows = b.vstack(&[30, 10, 20], 5);
+        assert_eq!(rows[1], Bounds::new(10, 55, 100, 10));
+        assert_eq!(rows[2].bottom(), 90);
+        assert_eq!(stack_height(&[30, 10, 20], 5), 70);
+        assert_eq!(stack_height(&[], 5), 0);
+
+        let columns = b.hstack(&[40, 40], 20);
+        assert_eq!(columns[1], Bounds::new(70, 20, 40, 200));
+    }
+
+    #[test]
+    fn test_grid() {
+        let cells = Bounds::new(0, 10, 101, 50).grid(2, 2);
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[0], Bounds::new(0, 10, 50, 25));
+        assert_eq!(cells[3], Bounds::new(50, 35, 50, 25));
+        assert!(Bounds::from_size((10, 10)).grid(0, 3).is_empty());
+    }
+}
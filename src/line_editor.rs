@@ -0,0 +1,399 @@
+/// single line of editable text with a cursor, plus the history and tab
+/// completion that go with it, the text entry part of the console.
+///
+/// cursor is a byte index into text and always sits on a char boundary,
+/// so any unicode can be typed and edited.
+#[derive(Clone, Debug, Default)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// text to the left of the cursor, used to place the caret.
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    /// replaces everything with text and puts the cursor at the end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = String::from(text);
+        self.cursor = self.text.len();
+    }
+
+    /// empties the line and hands back what was in it.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    /// inserts at the cursor, control characters are dropped.
+    pub fn insert_str(&mut self, s: &str) {
+        for c in s.chars().filter(|c| !c.is_control()) {
+            self.text.insert(self.cursor, c);
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .chars()
+            .next_back()
+            .map_or(self.cursor, |c| self.cursor - c.len_utf8())
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// start of the word to the left of the cursor, skipping any spaces first.
+    fn prev_word_boundary(&self) -> usize {
+        let before = &self.text[..self.cursor];
+        let trimmed = before.trim_end();
+        match trimmed.rfind(char::is_whitespace) {
+            Some(i) => i + trimmed[i..].chars().next().map_or(1, |c| c.len_utf8()),
+            None => 0,
+        }
+    }
+
+    /// end of the word to the right of the cursor, skipping any spaces first.
+    fn next_word_boundary(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let skipped = after.len() - after.trim_start().len();
+        let word = &after[skipped..];
+        let word_len = word.find(char::is_whitespace).unwrap_or(word.len());
+        self.cursor + skipped + word_len
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.prev_boundary();
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = self.next_boundary();
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = self.prev_word_boundary();
+    }
+
+    pub fn word_right(&mut self) {
+        self.cursor = self.next_word_boundary();
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    /// removes the character before the cursor.
+    pub fn backspace(&mut self) {
+        let start = self.prev_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// removes the character under the cursor.
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    /// removes the word before the cursor.
+    pub fn delete_word_back(&mut self) {
+        let start = self.prev_word_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// removes the word after the cursor.
+    pub fn delete_word_forward(&mut self) {
+        let end = self.next_word_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    /// byte index where the word the cursor is on (or just after) starts.
+    pub fn word_start(&self) -> usize {
+        self.before_cursor()
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + 1)
+    }
+
+    /// swaps the text between start and the cursor for replacement,
+    /// leaving the cursor after it.
+    pub fn replace_before_cursor(&mut self, start: usize, replacement: &str) {
+        self.text.replace_range(start..self.cursor, replacement);
+        self.cursor = start + replacement.len();
+    }
+}
+
+/// previously entered lines, walked through with up and down.
+#[derive(Clone, Debug)]
+pub struct History {
+    entries: Vec<String>,
+    max_entries: usize,
+    /// index into entries being shown, None when back at the line being typed.
+    position: Option<usize>,
+    /// what was being typed before walking into the history.
+    draft: String,
+}
+
+impl History {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            max_entries,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// remembers a line, blank lines and repeats of the last line are skipped.
+    pub fn push(&mut self, line: &str) {
+        self.position = None;
+        if line.trim().is_empty() || self.entries.last().map(|l| l.as_str()) == Some(line) {
+            return;
+        }
+        self.entries.push(String::from(line));
+        if self.entries.len() > self.max_entries {
+            self.entries.remove(0);
+        }
+    }
+
+    /// steps back to an older line, current is what's typed right now.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = String::from(current);
+                self.entries.len() - 1
+            }
+            Some(0) => 0,
+            Some(i) => i - 1,
+        };
+        self.position = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// steps forward to a newer line, ending back at the draft.
+    pub fn next(&mut self) -> Option<&str> {
+        match self.position {
+            None => None,
+            Some(i) if i + 1 < self.entries.len() => {
+                self.position = Some(i + 1);
+                Some(&self.entries[i + 1])
+            }
+            Some(_) => {
+                self.position = None;
+                Some(&self.draft)
+            }
+        }
+    }
+}
+
+/// result of tab completing a word.
+#[derive(Debug, PartialEq)]
+pub struct Completion {
+    /// what the word should become, the longest prefix shared by every match.
+    pub text: String,
+    /// every candidate that matched, empty when there was a single match.
+    pub matches: Vec<String>,
+}
+
+/// completes prefix against candidates. a single match is finished off with
+/// a trailing space, several matches are extended as far as they agree.
+pub fn complete<'a, I>(prefix: &str, candidates: I) -> Option<Completion>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(prefix))
+        .map(String::from)
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    match matches.len() {
+        0 => None,
+        1 => Some(Completion {
+            text: format!("{} ", matches[0]),
+            matches: Vec::new(),
+        }),
+        _ => {
+            let mut shared = matches[0].clone();
+            for m in matches.iter().skip(1) {
+                let common = shared
+                    .char_indices()
+                    .zip(m.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(shared.len().min(m.len()), |((i, _), _)| i);
+                shared.truncate(common);
+            }
+            Some(Completion {
+                text: shared,
+                matches,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> LineEditor {
+        let mut e = LineEditor::new();
+        e.set_text(text);
+        e
+    }
+
+    #[test]
+    fn test_insert_at_cursor() {
+        let mut e = editor("spawn 12");
+        e.home();
+        e.right();
+        e.insert_str("X\n");
+        assert_eq!(e.text(), "sXpawn 12");
+        assert_eq!(e.before_cursor(), "sX");
+    }
+
+    #[test]
+    fn test_unicode_editing() {
+        let mut e = editor("héllo wörld");
+        e.left();
+        e.left();
+        e.left();
+        assert_eq!(e.before_cursor(), "héllo wö");
+        e.backspace();
+        assert_eq!(e.text(), "héllo wrld");
+        e.delete();
+        assert_eq!(e.text(), "héllo wld");
+        e.insert_str("ö→");
+        assert_eq!(e.text(), "héllo wö→ld");
+    }
+
+    #[test]
+    fn test_word_deletion() {
+        let mut e = editor("give lives  10  ");
+        e.delete_word_back();
+        assert_eq!(e.text(), "give lives  ");
+        e.delete_word_back();
+        assert_eq!(e.text(), "give ");
+
+        let mut e = editor("set thrust 4");
+        e.home();
+        e.word_right();
+        assert_eq!(e.before_cursor(), "set");
+        e.delete_word_forward();
+        assert_eq!(e.text(), "set 4");
+        e.word_left();
+        assert_eq!(e.cursor(), 0);
+    }
+
+    #[test]
+    fn test_history_walk() {
+        let mut history = History::new(10);
+        history.push("god");
+        history.push("");
+        history.push("wave 2");
+        history.push("wave 2");
+
+        assert_eq!(history.previous("sp"), Some("wave 2"));
+        assert_eq!(history.previous("ignored"), Some("god"));
+        assert_eq!(history.previous("ignored"), Some("god"));
+        assert_eq!(history.next(), Some("wave 2"));
+        assert_eq!(history.next(), Some("sp"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn test_history_bounded() {
+        let mut history = History::new(2);
+        history.push("one");
+        history.push("two");
+        history.push("three");
+        assert_eq!(history.previous(""), Some("three"));
+        assert_eq!(history.previous(""), Some("two"));
+        assert_eq!(history.previous(""), Some("two"));
+        assert_eq!(History::new(2).previous(""), None);
+    }
+
+    #[test]
+    fn test_complete() {
+        let names = ["set", "seed", "spawn", "god"];
+        assert_eq!(
+            complete("g", names.iter().copied()),
+            Some(Completion {
+                text: String::from("god "),
+                matches: vec![]
+            })
+        );
+        assert_eq!(
+            complete("s", names.iter().copied()),
+            Some(Completion {
+                text: String::from("s"),
+                matches: vec![
+                    String::from("seed"),
+                    String::from("set"),
+                    String::from("spawn")
+                ]
+            })
+        );
+        assert_eq!(complete("se", names.iter().copied()).unwrap().text, "se");
+        assert_eq!(complete("x", names.iter().copied()), None);
+        assert_eq!(
+            complete("fire", ["fire_rate", "fire_cooldown"].iter().copied())
+                .unwrap()
+                .text,
+            "fire_"
+        );
+    }
+
+    #[test]
+    fn test_replace_word() {
+        let mut e = editor("set thr");
+        let start = e.word_start();
+        assert_eq!(start, 4);
+        e.replace_before_cursor(start, "thrust ");
+        assert_eq!(e.text(), "set thrust ");
+        assert_eq!(e.cursor(), e.text().len());
+    }
+
+    #[test]
+    fn test_edges_do_nothing() {
+        let mut e = editor("");
+        e.backspace();
+        e.delete();
+        e.left();
+        e.right();
+        e.delete_word_back();
+        e.delete_word_forward();
+        assert_eq!(e.text(), "");
+        assert_eq!(e.cursor(), 0);
+
+        let mut e = editor("abc");
+        e.delete();
+        assert_eq!(e.text(), "abc");
+        assert_eq!(e.take(), "abc");
+        assert_eq!(e.cursor(), 0);
+    }
+}
@@ -0,0 +1,255 @@
+/// game wide logging. records are filtered per category, echoed to stdout
+/// and, once capturing is turned on, queued up for the console to show.
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// categories the game logs under, anything else is still logged but
+/// can't be given its own filter from the console.
+pub const CATEGORIES: &[&str] = &["bot", "config", "highscore", "input", "render"];
+
+/// queued records past this are dropped oldest first if nobody drains them.
+const MAX_PENDING: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "debug" => Ok(Level::Debug),
+            "info" => Ok(Level::Info),
+            "warn" => Ok(Level::Warn),
+            "error" => Ok(Level::Error),
+            _ => Err(format!("'{}' is not debug, info, warn or error", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub level: Level,
+    pub category: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.level, self.category, self.message)
+    }
+}
+
+/// filters and the queue of records waiting for the console.
+pub struct Logger {
+    /// level used by categories without a filter of their own.
+    default_level: Level,
+    filters: BTreeMap<&'static str, Level>,
+    /// print records to stdout as they're logged.
+    echo: bool,
+    /// queue records for drain, off until something is there to drain them.
+    capture: bool,
+    pending: VecDeque<Record>,
+}
+
+impl Logger {
+    pub const fn new() -> Self {
+        Self {
+            default_level: Level::Info,
+            filters: BTreeMap::new(),
+            echo: true,
+            capture: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn level(&self, category: &str) -> Level {
+        self.filters
+            .get(category)
+            .copied()
+            .unwrap_or(self.default_level)
+    }
+
+    pub fn enabled(&self, level: Level, category: &str) -> bool {
+        level >= self.level(category)
+    }
+
+    pub fn default_level(&self) -> Level {
+        self.default_level
+    }
+
+    /// sets the level every category without its own filter uses.
+    pub fn set_default_level(&mut self, level: Level) {
+        self.default_level = level;
+    }
+
+    /// sets the level for one of CATEGORIES.
+    pub fn set_level(&mut self, category: &str, level: Level) -> Result<(), String> {
+        let category = CATEGORIES
+            .iter()
+            .find(|c| **c == category)
+            .ok_or_else(|| format!("unknown log category '{}'", category))?;
+        self.filters.insert(*category, level);
+        Ok(())
+    }
+
+    /// drops a category's own filter so it follows the default again.
+    pub fn clear_level(&mut self, category: &str) {
+        self.filters.remove(category);
+    }
+
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    pub fn set_capture(&mut self, capture: bool) {
+        self.capture = capture;
+        if !capture {
+            self.pending.clear();
+        }
+    }
+
+    pub fn log(&mut self, level: Level, category: &'static str, message: String) {
+        if !self.enabled(level, category) {
+            return;
+        }
+        let record = Record {
+            level,
+            category,
+            message,
+        };
+        if self.echo {
+            println!("{}", record);
+        }
+        if self.capture {
+            if self.pending.len() >= MAX_PENDING {
+                self.pending.pop_front();
+            }
+            self.pending.push_back(record);
+        }
+    }
+
+    /// records logged since the last drain, oldest first.
+    pub fn drain(&mut self) -> Vec<Record> {
+        self.pending.drain(..).collect()
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static LOGGER: Mutex<Logger> = Mutex::new(Logger::new());
+
+/// runs f with the game's logger.
+pub fn with_logger<T, F: FnOnce(&mut Logger) -> T>(f: F) -> T {
+    let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut logger)
+}
+
+pub fn log<S: Into<String>>(level: Level, category: &'static str, message: S) {
+    with_logger(|logger| logger.log(level, category, message.into()));
+}
+
+pub fn debug<S: Into<String>>(category: &'static str, message: S) {
+    log(Level::Debug, category, message);
+}
+
+pub fn info<S: Into<String>>(category: &'static str, message: S) {
+    log(Level::Info, category, message);
+}
+
+pub fn warn<S: Into<String>>(category: &'static str, message: S) {
+    log(Level::Warn, category, message);
+}
+
+pub fn error<S: Into<String>>(category: &'static str, message: S) {
+    log(Level::Error, category, message);
+}
+
+/// records waiting for the console.
+pub fn drain() -> Vec<Record> {
+    with_logger(|logger| logger.drain())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet_logger() -> Logger {
+        let mut logger = Logger::new();
+        logger.set_echo(false);
+        logger.set_capture(true);
+        logger
+    }
+
+    #[test]
+    fn test_category_filters() {
+        let mut logger = quiet_logger();
+        assert!(!logger.enabled(Level::Debug, "input"));
+        assert!(logger.enabled(Level::Info, "input"));
+
+        logger.set_level("input", Level::Debug).unwrap();
+        logger.set_default_level(Level::Error);
+        assert!(logger.enabled(Level::Debug, "input"));
+        assert!(!logger.enabled(Level::Warn, "bot"));
+        assert!(logger.set_level("nope", Level::Debug).is_err());
+
+        logger.clear_level("input");
+        assert_eq!(logger.level("input"), Level::Error);
+    }
+
+    #[test]
+    fn test_capture_and_drain() {
+        let mut logger = quiet_logger();
+        logger.log(Level::Debug, "input", String::from("key down"));
+        logger.log(Level::Warn, "bot", String::from("no reply"));
+        let records = logger.drain();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].to_string(), "[warn] bot: no reply");
+        assert!(logger.drain().is_empty());
+
+        logger.set_capture(false);
+        logger.log(Level::Error, "render", String::from("lost"));
+        assert!(logger.drain().is_empty());
+    }
+
+    #[test]
+    fn test_pending_bounded() {
+        let mut logger = quiet_logger();
+        for i in 0..MAX_PENDING + 5 {
+            logger.log(Level::Info, "bot", i.to_string());
+        }
+        let records = logger.drain();
+        assert_eq!(records.len(), MAX_PENDING);
+        assert_eq!(records[0].message, "5");
+    }
+
+    #[test]
+    fn test_level_parse() {
+        assert_eq!("warn".parse::<Level>(), Ok(Level::Warn));
+        assert!("loud".parse::<Level>().is_err());
+        assert_eq!(Level::Debug.to_string(), "debug");
+    }
+}
@@ -1,18 +1,166 @@
 #![allow(dead_code)]
 
 mod asteroids;
+mod batch;
+mod bindings;
+mod bot_protocol;
 mod circles;
 mod collision;
+mod commands;
 mod console;
+mod controller;
+mod cvars;
+mod highscore;
+mod hud;
+mod inspector;
+mod layout;
+mod line_editor;
+mod log;
+mod menu;
+mod mouse;
+mod name_entry;
+mod simulation;
+mod text_cache;
+mod theme;
+mod tournament;
+mod toast;
+mod tween;
 mod utils;
 mod widget;
+mod widget_stack;
 
+use std::cell::RefCell;
 use std::path::Path;
+use std::rc::Rc;
+use std::time::Instant;
 use sdl2;
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
 
+use bindings::Bindings;
+use commands::{CommandContext, CommandRegistry};
+use console::Console;
+use cvars::CvarRegistry;
+use highscore::{HighScore, HighScoreTable};
+use hud::Hud;
+use inspector::Inspector;
+use menu::{Menu, MenuAction};
+use name_entry::NameEntry;
+use text_cache::TextCache;
+use theme::Theme;
+use toast::{GameWatch, Toasts};
+use widget::{DrawableWidget, Widget};
+use widget_stack::WidgetStack;
+
+/// commands that run without opening a window, given the rest of the args.
+type HeadlessCommand = fn(&[String]) -> Result<(), String>;
+
 fn main() -> () {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // headless commands never open a window.
+    let headless: Option<HeadlessCommand> = match args.first().map(|a| a.as_str()) {
+        Some("tournament") => Some(tournament::run_from_args),
+        Some("batch") => Some(batch::run_from_args),
+        Some("bench") => Some(simulation::run_bench_from_args),
+        _ => None,
+    };
+    if let Some(command) = headless {
+        if let Err(e) = command(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // pick who is flying the ship, defaults to the keyboard.
+    // rasteroids --controller bot
+    let mut controller_spec = String::from("keyboard");
+    // rasteroids --theme high_contrast, or a theme file.
+    let mut theme_spec: Option<String> = None;
+    let mut cvars = CvarRegistry::with_game_cvars();
+    // log records are shown in the console from here on.
+    log::with_logger(|logger| logger.set_capture(true));
+    if Path::new(cvars::CONFIG_FILE).exists() {
+        match cvars.load(Path::new(cvars::CONFIG_FILE)) {
+            Ok(errors) => {
+                for e in errors {
+                    log::warn("config", format!("{}: {}", cvars::CONFIG_FILE, e));
+                }
+            }
+            Err(e) => log::warn("config", e),
+        }
+    }
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--console-no-pause" => {
+                cvars.set("console_pause", "off").unwrap();
+            }
+            "--controller" => match args.next() {
+                Some(spec) => controller_spec = spec,
+                None => {
+                    eprintln!("--controller needs a value");
+                    std::process::exit(1);
+                }
+            },
+            "--theme" => match args.next() {
+                Some(spec) => theme_spec = Some(spec),
+                None => {
+                    eprintln!("--theme needs a value");
+                    std::process::exit(1);
+                }
+            },
+            _ => {
+                eprintln!("unknown argument: {}", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut controller = match controller::from_spec(&controller_spec) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // a theme asked for on the command line has to exist, a broken theme
+    // file just falls back to the classic look.
+    let mut theme = match theme_spec {
+        Some(spec) => match Theme::from_spec(&spec) {
+            Ok((theme, errors)) => {
+                for e in errors {
+                    log::warn("config", format!("{}: {}", spec, e));
+                }
+                theme
+            }
+            Err(e) => {
+                eprintln!("{}, built in themes are {}", e, theme::BUILTIN_THEMES.join(", "));
+                std::process::exit(1);
+            }
+        },
+        None if Path::new(theme::THEME_FILE).exists() => {
+            match Theme::load(Path::new(theme::THEME_FILE)) {
+                Ok((theme, errors)) => {
+                    for e in errors {
+                        log::warn("config", format!("{}: {}", theme::THEME_FILE, e));
+                    }
+                    theme
+                }
+                Err(e) => {
+                    log::warn("config", e);
+                    Theme::classic()
+                }
+            }
+        }
+        None => Theme::classic(),
+    };
+    let game_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    theme.font = game_dir.join(&theme.font);
+
     let sdl_context = sdl2::init().unwrap();
     let mut event_pump = sdl_context.event_pump().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
@@ -23,6 +171,7 @@ fn main() -> () {
     let window = video_subsystem
         .window("RAsteroids", window_width, window_height)
         .position_centered()
+        .resizable()
         .build()
         .unwrap();
 
@@ -43,44 +192,255 @@ fn main() -> () {
     let frame_per_second_target = 60;
     let _milliseconds_per_frame = 1000.0 / frame_per_second_target as f32;
 
-    // need some sort of stateful item for what has focus.
-    // need to then pass the event to w/e item has current focuse
-    // then each item has a sort of "back out" option.
+    let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string()).unwrap();
 
-    // w/e widget has focus is the current "top" widget.
-    // widget_stack.push(Box::new(Console::new()));
+    // lines entered into the console, run as commands once events are handled.
+    let submitted_lines = RefCell::new(Vec::new());
+    let console_callback = |line: String| submitted_lines.borrow_mut().push(line);
+    let commands = CommandRegistry::with_builtins();
+    let text_cache = Rc::new(RefCell::new(TextCache::new(&ttf_context, &texture_creator)));
+    // widgets expect their font to load, so a theme font that doesn't is
+    // swapped for the classic one here rather than failing in each widget.
+    if let Err(e) = text_cache
+        .borrow_mut()
+        .load_font(&theme.font, theme.small_size)
+    {
+        log::warn(
+            "config",
+            format!("failed to load font {}: {}", theme.font.display(), e),
+        );
+        theme.font = game_dir.join(Theme::classic().font);
+    }
+    let mut console = Console::new(
+        text_cache.clone(),
+        &theme,
+        &console_callback,
+    );
+    console.set_size(window_width, window_height / 2);
 
-    // let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string()).unwrap();
-    // let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    // p.push("lazy.ttf");
+    console.set_completions(
+        commands.names().map(String::from).collect(),
+        cvars
+            .names()
+            .chain(log::CATEGORIES.iter().copied())
+            .map(String::from)
+            .collect(),
+    );
 
     let mut game_state = asteroids::game_init();
+    cvars.apply(&mut game_state.tuning);
 
-    let game_input = asteroids::GameInput {
-        rotation: 1.0,
-        shoot: true,
-        thrusters: false,
-    };
+    // saved key bindings first so autoexec can change them, though only
+    // bindings made from the console are saved back.
+    let mut bindings = Bindings::new();
+    for script in [bindings::BINDS_FILE, commands::AUTOEXEC_FILE].iter() {
+        if Path::new(script).exists() {
+            let mut ctx = CommandContext::new(&mut game_state, &mut cvars, &mut bindings);
+            let exec = format!("exec {}", script);
+            for output in commands.execute_to_lines(&mut ctx, &exec) {
+                console.print(output);
+            }
+        }
+        if *script == bindings::BINDS_FILE {
+            bindings.mark_saved();
+            bindings.set_persistent(false);
+        }
+    }
+    bindings.set_persistent(true);
+
+    // the console stays alive while closed so it keeps its scrollback,
+    // opening it just puts it on the widget stack.
+    let console = Rc::new(RefCell::new(console));
+    let mut console_id = None;
+
+    // picked menu items, acted on once events are handled.
+    let menu_actions = RefCell::new(Vec::new());
+    let menu_callback = |action: MenuAction| menu_actions.borrow_mut().push(action);
+    let mut screen_size = (window_width, window_height);
+
+    let highscore_path = Path::new(highscore::HIGHSCORE_FILE);
+    let (mut high_scores, errors) = HighScoreTable::load(highscore_path);
+    for e in errors {
+        log::warn("highscore", format!("{}: {}", highscore::HIGHSCORE_FILE, e));
+    }
+    // names typed for a new high score, saved once events are handled.
+    let entered_names = RefCell::new(Vec::new());
+    let name_callback = |name: String| entered_names.borrow_mut().push(name);
+    let mut name_entry_id = None;
+
+    // drawn over the game but under every widget, never has focus.
+    let mut hud = Hud::new(text_cache.clone(), &theme, screen_size);
+    // pop up messages, drawn like the hud so they never take focus.
+    let mut toasts = Toasts::new(text_cache.clone(), &theme, screen_size);
+    let mut game_watch = GameWatch::new(&game_state);
+
+    // debug panel for looking at and changing entities, toggled with f3.
+    let entity_edits = RefCell::new(Vec::new());
+    let edit_callback = |edit| entity_edits.borrow_mut().push(edit);
+    let inspector = Rc::new(RefCell::new(Inspector::new(
+        text_cache.clone(),
+        &theme,
+        screen_size,
+        &edit_callback,
+    )));
+    let mut inspector_id = None;
+
+    // whatever widget is on top of the stack has focus, the game only gets
+    // events none of them want.
+    let mut widgets = WidgetStack::new();
+    let mut last_frame = Instant::now();
+
+    // the game waits behind the title screen until play is picked.
+    let title = Menu::title_screen(text_cache.clone(), &theme, screen_size, &menu_callback);
+    let mut menu_id = Some(widgets.push(Box::new(title), 0, 0));
 
     // hold the app and wait for user to quit.
     'holding_loop: loop {
         canvas.clear();
+        let console_open = console_id.is_some_and(|id| widgets.contains(id));
+        let menu_open = menu_id.is_some_and(|id| widgets.contains(id));
+        let name_entry_open = name_entry_id.is_some_and(|id| widgets.contains(id));
+
+        let console_pauses_game = cvars.get_bool("console_pause").unwrap_or(true);
+        let paused = menu_open || name_entry_open || (console_open && console_pauses_game);
+        if !paused {
+            let game_input = controller.decide(&game_state);
+            asteroids::game_update(&mut game_state, 0.1, &game_input);
+
+            // a good enough score asks for a name before the game over menu.
+            if game_state.game_over && high_scores.qualifies(game_state.score) {
+                let name_entry = NameEntry::new(
+                    game_state.score,
+                    text_cache.clone(),
+                    &theme,
+                    screen_size,
+                    &name_callback,
+                );
+                name_entry_id = Some(widgets.push(Box::new(name_entry), 0, 0));
+            } else if game_state.game_over {
+                let game_over = Menu::game_over(
+                    &game_state,
+                    None,
+                    text_cache.clone(),
+                    &theme,
+                    screen_size,
+                    &menu_callback,
+                );
+                menu_id = Some(widgets.push(Box::new(game_over), 0, 0));
+            }
+        }
+        for message in game_watch.changes(&game_state) {
+            toasts.queue.push(message);
+        }
+
+        if inspector_id.is_some_and(|id| widgets.contains(id)) {
+            inspector.borrow_mut().set_game_state(&game_state);
+        }
 
-        game_state = asteroids::game_update(game_state, 0.1, &game_input);
+        let dt = last_frame.elapsed().as_secs_f32();
+        last_frame = Instant::now();
+        widgets.update(dt);
+        hud.update(dt);
+        toasts.update(dt);
+        hud.set_game_state(&game_state);
+        hud.show_fps = cvars.get_bool("show_fps").unwrap_or(false);
 
-        asteroids::game_sdl2_render(&game_state, &mut canvas, &image_resources);
+        asteroids::game_sdl2_render(&game_state, &mut canvas, &image_resources, theme.background);
+        hud.draw(&mut canvas, 0, 0);
+        toasts.draw(&mut canvas, 0, 0);
+        widgets.draw(&mut canvas);
         canvas.present();
+        text_cache.borrow_mut().end_frame();
 
         std::thread::sleep(std::time::Duration::from_millis(10));
 
         // event processing which is sent directly to the top layer widget.
         for event in event_pump.poll_iter() {
+            let handled = match event {
+                // backquote opens and closes the console whatever has focus.
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backquote),
+                    repeat,
+                    ..
+                } => {
+                    if !repeat {
+                        match console_id.filter(|id| widgets.contains(*id)) {
+                            Some(id) => {
+                                widgets.remove(id);
+                            }
+                            None => {
+                                console.borrow_mut().slide_in();
+                                console_id = Some(widgets.push(Box::new(console.clone()), 0, 0));
+                            }
+                        }
+                    }
+                    true
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F3),
+                    repeat,
+                    ..
+                } => {
+                    if !repeat {
+                        match inspector_id.filter(|id| widgets.contains(*id)) {
+                            Some(id) => {
+                                widgets.remove(id);
+                            }
+                            None => {
+                                inspector.borrow_mut().set_game_state(&game_state);
+                                let handle = Box::new(inspector.clone());
+                                inspector_id = Some(widgets.push(handle, 0, 0));
+                            }
+                        }
+                    }
+                    true
+                }
+                // the backquote that opened the console also arrives as text.
+                Event::TextInput { ref text, .. } if text == "`" => true,
+                _ => widgets.handle_event(&event),
+            };
+
+            if !handled {
+                match event {
+                    // escape with nothing else open pauses the game.
+                    Event::KeyDown {
+                        keycode: Some(Keycode::Escape),
+                        repeat: false,
+                        ..
+                    } => {
+                        let pause = Menu::pause(text_cache.clone(), &theme, screen_size, &menu_callback);
+                        menu_id = Some(widgets.push(Box::new(pause), 0, 0));
+                    }
+                    // bound keys run their command instead of reaching the controller.
+                    Event::KeyDown {
+                        keycode: Some(k),
+                        repeat,
+                        ..
+                    } if bindings.command_for(&k.name()).is_some() => {
+                        if !repeat {
+                            let command = bindings.command_for(&k.name()).unwrap_or_default();
+                            submitted_lines.borrow_mut().push(String::from(command));
+                        }
+                    }
+                    _ => controller.handle_event(&event),
+                }
+            }
+
             match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
+                Event::Quit { .. } => break 'holding_loop,
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(width, height),
                     ..
-                } => break 'holding_loop,
+                } => {
+                    screen_size = (width as u32, height as u32);
+                    widgets.resize(screen_size.0, screen_size.1);
+                    hud.resize(screen_size.0, screen_size.1);
+                    toasts.resize(screen_size.0, screen_size.1);
+                    // the console and inspector aren't on the stack while closed but
+                    // still need to know.
+                    console.borrow_mut().resize(screen_size.0, screen_size.1);
+                    inspector.borrow_mut().resize(screen_size.0, screen_size.1);
+                }
                 Event::KeyUp {
                     timestamp,
                     window_id: _,
@@ -89,20 +449,16 @@ fn main() -> () {
                     keymod,
                     repeat,
                 } => {
-                    println!(
-                        "Up timestamp: {}, repeat: {}, keycode: {}, keymode: {}",
-                        timestamp,
-                        repeat,
-                        keycode.unwrap(),
-                        keymod
+                    log::debug(
+                        "input",
+                        format!(
+                            "Up timestamp: {}, repeat: {}, keycode: {:?}, keymode: {}",
+                            timestamp,
+                            repeat,
+                            keycode,
+                            keymod
+                        ),
                     );
-
-                    match keycode {
-                        Some(Keycode::Backquote) => {
-                            // todo: display / push the console onto the stack.
-                        }
-                        _ => (),
-                    };
                 }
                 Event::KeyDown {
                     timestamp,
@@ -112,23 +468,23 @@ fn main() -> () {
                     keymod,
                     repeat,
                 } => {
-                    println!(
-                        "Down timestamp: {}, repeat: {}, keycode: {}, keymode: {}",
-                        timestamp,
-                        repeat,
-                        keycode.unwrap(),
-                        keymod
+                    log::debug(
+                        "input",
+                        format!(
+                            "Down timestamp: {}, repeat: {}, keycode: {:?}, keymode: {}",
+                            timestamp,
+                            repeat,
+                            keycode,
+                            keymod
+                        ),
                     );
-                    match keycode {
-                        Some(Keycode::Space) => {
-                            canvas.clear();
-                        }
-                        _ => (),
+                    if keycode == Some(Keycode::Space) {
+                        canvas.clear();
                     }
                 }
 
                 Event::MultiGesture { .. } => {
-                    println!("Got a multigesture");
+                    log::debug("input", "Got a multigesture");
                 }
                 Event::MouseButtonDown { .. } => (),
                 _ => {
@@ -136,5 +492,113 @@ fn main() -> () {
                 }
             }
         }
+
+        let names: Vec<String> = entered_names.borrow_mut().drain(..).collect();
+        for name in names {
+            let rank = high_scores.insert(HighScore::from_game(&game_state, &name));
+            match high_scores.save(highscore_path) {
+                Ok(()) => toasts.queue.push("High score saved"),
+                Err(e) => log::error("highscore", e),
+            }
+            if let Some(id) = name_entry_id.take() {
+                widgets.remove(id);
+            }
+            let game_over = Menu::game_over(
+                &game_state,
+                rank,
+                text_cache.clone(),
+                &theme,
+                screen_size,
+                &menu_callback,
+            );
+            menu_id = Some(widgets.push(Box::new(game_over), 0, 0));
+        }
+
+        // only ask sdl for text input while something could be typed.
+        let console_open = console_id.is_some_and(|id| widgets.contains(id));
+        let name_entry_open = name_entry_id.is_some_and(|id| widgets.contains(id));
+        let inspector_open = inspector_id.is_some_and(|id| widgets.contains(id));
+        let wants_text = console_open || name_entry_open || inspector_open;
+        let text_input = video_subsystem.text_input();
+        if wants_text && !text_input.is_active() {
+            text_input.start();
+        } else if !wants_text && text_input.is_active() {
+            text_input.stop();
+        }
+
+        let edits: Vec<_> = entity_edits.borrow_mut().drain(..).collect();
+        for edit in edits {
+            if let Err(e) = game_state.set_entity_field(edit.id, edit.field, edit.value) {
+                inspector.borrow_mut().set_status(e);
+            }
+        }
+
+        let actions: Vec<MenuAction> = menu_actions.borrow_mut().drain(..).collect();
+        for action in actions {
+            let next_menu = match action {
+                MenuAction::Play | MenuAction::Restart => {
+                    game_state = asteroids::game_init();
+                    cvars.apply(&mut game_state.tuning);
+                    None
+                }
+                MenuAction::Resume => None,
+                MenuAction::Quit => break 'holding_loop,
+                MenuAction::QuitToMenu | MenuAction::Back => Some(Menu::title_screen(
+                    text_cache.clone(),
+                    &theme,
+                    screen_size,
+                    &menu_callback,
+                )),
+                MenuAction::Settings => {
+                    let mut lines: Vec<String> = cvars
+                        .iter()
+                        .map(|c| format!("{} {}", c.name, c.value()))
+                        .collect();
+                    lines.push(String::from("change these from the console with set"));
+                    Some(Menu::info(
+                        "Settings",
+                        lines,
+                        text_cache.clone(),
+                        &theme,
+                        screen_size,
+                        &menu_callback,
+                    ))
+                }
+                MenuAction::HighScores => Some(Menu::info(
+                    "High Scores",
+                    high_scores.lines(),
+                    text_cache.clone(),
+                    &theme,
+                    screen_size,
+                    &menu_callback,
+                )),
+            };
+            if let Some(id) = menu_id.take() {
+                widgets.remove(id);
+            }
+            if let Some(menu) = next_menu {
+                menu_id = Some(widgets.push(Box::new(menu), 0, 0));
+            }
+        }
+
+        let lines: Vec<String> = submitted_lines.borrow_mut().drain(..).collect();
+        for line in lines {
+            let mut ctx = CommandContext::new(&mut game_state, &mut cvars, &mut bindings);
+            for output in commands.execute_to_lines(&mut ctx, &line) {
+                console.borrow_mut().print(output);
+            }
+            for message in ctx.take_notifications() {
+                toasts.queue.push(message);
+            }
+        }
+        for record in log::drain() {
+            console.borrow_mut().print_record(&record);
+        }
+    }
+
+    if bindings.is_changed() {
+        if let Err(e) = bindings.save(Path::new(bindings::BINDS_FILE)) {
+            eprintln!("{}", e);
+        }
     }
 }
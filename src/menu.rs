@@ -0,0 +1,437 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::asteroids::GameState;
+use crate::layout::{stack_height, Anchor, Bounds};
+use crate::mouse::{self, Button};
+use crate::simulation::GameStats;
+use crate::text_cache::{FontId, TextCache};
+use crate::theme::Theme;
+use crate::tween::{Easing, Tween};
+use crate::widget::{DrawableWidget, EventResult, Widget};
+
+/// seconds a menu takes to slide into place.
+const SLIDE_SECONDS: f32 = 0.25;
+/// how far above its resting place a menu starts sliding from.
+const SLIDE_DISTANCE: f32 = 40.0;
+
+/// what picking a menu item asks the game to do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuAction {
+    Play,
+    Settings,
+    HighScores,
+    Quit,
+    Resume,
+    Restart,
+    QuitToMenu,
+    /// leave a screen that only shows information.
+    Back,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MenuItem {
+    pub label: String,
+    pub action: MenuAction,
+}
+
+/// items with one selected, moving off either end wraps around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MenuList {
+    items: Vec<MenuItem>,
+    selected: usize,
+}
+
+impl MenuList {
+    pub fn new(items: &[(&str, MenuAction)]) -> Self {
+        Self {
+            items: items
+                .iter()
+                .map(|(label, action)| MenuItem {
+                    label: String::from(*label),
+                    action: *action,
+                })
+                .collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.selected = index;
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+        }
+    }
+
+    /// action of the selected item.
+    pub fn action(&self) -> Option<MenuAction> {
+        self.items.get(self.selected).map(|i| i.action)
+    }
+}
+
+/// a full screen menu: a title, some lines of info and a list of items
+/// picked with up, down and enter.
+pub struct Menu<'ttf, 'tc, 'callback> {
+    title: String,
+    /// lines shown between the title and the items, e.g. the final score.
+    lines: Vec<String>,
+    list: MenuList,
+    /// a clickable area over each item, placed when the menu is drawn.
+    buttons: Vec<Button>,
+    /// what escape does, None lets it back out of the menu.
+    back_action: Option<MenuAction>,
+
+    text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+    title_font: FontId,
+    item_font: FontId,
+    theme: Theme,
+    width: u32,
+    height: u32,
+    /// vertical offset while sliding in.
+    slide: Tween,
+
+    /// called with the action of whichever item is picked.
+    action_callback: &'callback dyn Fn(MenuAction),
+}
+
+impl<'ttf, 'tc, 'callback> Menu<'ttf, 'tc, 'callback> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        title: &str,
+        lines: Vec<String>,
+        list: MenuList,
+        back_action: Option<MenuAction>,
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        action_callback: &'callback dyn Fn(MenuAction),
+    ) -> Self {
+        let title_font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.title_size)
+            .unwrap();
+        let item_font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.text_size)
+            .unwrap();
+        Self {
+            title: String::from(title),
+            lines,
+            buttons: vec![Button::default(); list.items().len()],
+            list,
+            back_action,
+            text,
+            title_font,
+            item_font,
+            theme: theme.clone(),
+            width: size.0,
+            height: size.1,
+            slide: Tween::new(-SLIDE_DISTANCE, 0.0, SLIDE_SECONDS, Easing::EaseOut),
+            action_callback,
+        }
+    }
+
+    /// first thing shown when the game starts, escape quits.
+    pub fn title_screen(
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        action_callback: &'callback dyn Fn(MenuAction),
+    ) -> Self {
+        let list = MenuList::new(&[
+            ("Play", MenuAction::Play),
+            ("Settings", MenuAction::Settings),
+            ("High Scores", MenuAction::HighScores),
+            ("Quit", MenuAction::Quit),
+        ]);
+        Self::new(
+            "RAsteroids",
+            Vec::new(),
+            list,
+            Some(MenuAction::Quit),
+            text,
+            theme,
+            size,
+            action_callback,
+        )
+    }
+
+    /// shown over a paused game, escape resumes.
+    pub fn pause(
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        action_callback: &'callback dyn Fn(MenuAction),
+    ) -> Self {
+        let list = MenuList::new(&[
+            ("Resume", MenuAction::Resume),
+            ("Restart", MenuAction::Restart),
+            ("Quit to Menu", MenuAction::QuitToMenu),
+        ]);
+        Self::new(
+            "Paused",
+            Vec::new(),
+            list,
+            None,
+            text,
+            theme,
+            size,
+            action_callback,
+        )
+    }
+
+    /// lines of information with nothing to pick but back.
+    pub fn info(
+        title: &str,
+        lines: Vec<String>,
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        action_callback: &'callback dyn Fn(MenuAction),
+    ) -> Self {
+        Self::new(
+            title,
+            lines,
+            MenuList::new(&[("Back", MenuAction::Back)]),
+            Some(MenuAction::Back),
+            text,
+            theme,
+            size,
+            action_callback,
+        )
+    }
+
+    /// the final score and stats of a finished game, escape goes to the title.
+    /// rank is where the score landed on the high score table, if it did.
+    pub fn game_over(
+        game_state: &GameState,
+        rank: Option<usize>,
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        action_callback: &'callback dyn Fn(MenuAction),
+    ) -> Self {
+        let stats = GameStats::from_state(game_state);
+        let mut lines = vec![
+            format!("Score {}", stats.score),
+            format!("Reached wave {}", game_state.wave),
+            format!(
+                "Hit {} of {} shots ({:.0}%)",
+                stats.shots_hit,
+                stats.shots_fired,
+                stats.accuracy() * 100.0
+            ),
+            format!("Survived {} ticks", stats.ticks),
+        ];
+        if let Some(rank) = rank {
+            lines.push(format!("New high score, number {} on the table", rank));
+        }
+        let list = MenuList::new(&[
+            ("Play Again", MenuAction::Restart),
+            ("Quit to Menu", MenuAction::QuitToMenu),
+        ]);
+        Self::new(
+            "Game Over",
+            lines,
+            list,
+            Some(MenuAction::QuitToMenu),
+            text,
+            theme,
+            size,
+            action_callback,
+        )
+    }
+}
+
+impl<'ttf, 'tc, 'callback> Widget for Menu<'ttf, 'tc, 'callback> {
+    fn update_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::KeyDown {
+                keycode: Some(k),
+                repeat,
+                ..
+            } => {
+                match k {
+                    Keycode::Up | Keycode::W => self.list.previous(),
+                    Keycode::Down | Keycode::S => self.list.next(),
+                    Keycode::Return | Keycode::KpEnter | Keycode::Space if !repeat => {
+                        if let Some(action) = self.list.action() {
+                            (self.action_callback)(action);
+                        }
+                    }
+                    Keycode::Escape if !repeat => match self.back_action {
+                        Some(action) => (self.action_callback)(action),
+                        None => return EventResult::Ignored,
+                    },
+                    _ => (),
+                }
+                // menus take over the keyboard, nothing underneath sees key presses.
+                EventResult::Handled
+            }
+            Event::TextInput { .. } => EventResult::Handled,
+            Event::MouseWheel { .. } => {
+                match mouse::wheel(&event) {
+                    Some(steps) if steps > 0 => self.list.previous(),
+                    Some(steps) if steps < 0 => self.list.next(),
+                    _ => (),
+                }
+                EventResult::Handled
+            }
+            // hovering an item selects it, clicking it picks it.
+            _ if mouse::position(&event).is_some() => {
+                let mut clicked = None;
+                for (index, button) in self.buttons.iter_mut().enumerate() {
+                    if button.handle_event(&event) {
+                        clicked = Some(index);
+                    }
+                    if button.is_hovered() {
+                        self.list.select(index);
+                    }
+                }
+                if let Some(action) = clicked.and_then(|_| self.list.action()) {
+                    (self.action_callback)(action);
+                }
+                EventResult::Handled
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.slide.update(dt);
+    }
+
+    fn get_current_string(&self) -> String {
+        self.list
+            .items()
+            .get(self.list.selected())
+            .map_or_else(String::new, |i| i.label.clone())
+    }
+}
+
+impl<'ttf, 'tc, 'callback> DrawableWidget for Menu<'ttf, 'tc, 'callback> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        let screen = Bounds::new(x as i32, y as i32, self.width, self.height);
+
+        // dim whatever is behind the menu.
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(self.theme.overlay);
+        canvas
+            .fill_rect(screen.to_rect())
+            .expect("Failed to dim behind menu");
+        canvas.set_blend_mode(BlendMode::None);
+
+        let mut text = self.text.borrow_mut();
+        let title_height = text.line_height(self.title_font);
+        let line_height = text.line_height(self.item_font);
+
+        // one row per thing drawn with a blank row under the title and the
+        // lines, the whole lot centred on screen.
+        let mut rows = vec![(self.title.clone(), self.title_font, self.theme.text)];
+        let mut heights = vec![2 * title_height];
+        for line in self.lines.iter() {
+            rows.push((line.clone(), self.item_font, self.theme.muted));
+            heights.push(line_height);
+        }
+        if !self.lines.is_empty() {
+            *heights.last_mut().unwrap() += line_height;
+        }
+        for (index, item) in self.list.items().iter().enumerate() {
+            rows.push(if index == self.list.selected() {
+                (
+                    format!("> {} <", item.label),
+                    self.item_font,
+                    self.theme.highlight,
+                )
+            } else {
+                (item.label.clone(), self.item_font, self.theme.muted)
+            });
+            heights.push(line_height * 3 / 2);
+        }
+
+        let block = screen.anchored((self.width, stack_height(&heights, 0)), Anchor::Centre);
+        let block = Bounds {
+            y: block.y + self.slide.value().round() as i32,
+            ..block
+        };
+        let row_bounds = block.vstack(&heights, 0);
+        for ((label, font, color), row) in rows.iter().zip(row_bounds.iter()) {
+            let size = (text.text_width(*font, label), text.line_height(*font));
+            let at = row.anchored(size, Anchor::Top);
+            text.draw(canvas, *font, label, *color, at.x, at.y)
+                .expect("Failed to draw menu");
+        }
+        // the whole row of an item is clickable, not just the letters.
+        let first_item = row_bounds.len() - self.buttons.len();
+        for (button, row) in self.buttons.iter_mut().zip(row_bounds[first_item..].iter()) {
+            button.bounds = *row;
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> MenuList {
+        MenuList::new(&[
+            ("Play", MenuAction::Play),
+            ("Settings", MenuAction::Settings),
+            ("Quit", MenuAction::Quit),
+        ])
+    }
+
+    #[test]
+    fn test_selection_wraps() {
+        let mut list = list();
+        assert_eq!(list.action(), Some(MenuAction::Play));
+        list.previous();
+        assert_eq!(list.action(), Some(MenuAction::Quit));
+        list.next();
+        list.next();
+        assert_eq!(list.action(), Some(MenuAction::Settings));
+        assert_eq!(list.items()[list.selected()].label, "Settings");
+    }
+
+    #[test]
+    fn test_select_out_of_range_ignored() {
+        let mut list = list();
+        list.select(2);
+        assert_eq!(list.action(), Some(MenuAction::Quit));
+        list.select(7);
+        assert_eq!(list.selected(), 2);
+
+        let mut empty = MenuList::new(&[]);
+        empty.next();
+        empty.previous();
+        assert_eq!(empty.action(), None);
+    }
+}
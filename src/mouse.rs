@@ -0,0 +1,170 @@
+/// mouse handling shared by widgets: where an event happened, wheel
+/// scrolling and buttons that can be hovered and clicked.
+use sdl2::event::Event;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+
+use crate::layout::Bounds;
+
+/// where the pointer was for motion and button events.
+pub fn position(event: &Event) -> Option<(i32, i32)> {
+    match *event {
+        Event::MouseMotion { x, y, .. }
+        | Event::MouseButtonDown { x, y, .. }
+        | Event::MouseButtonUp { x, y, .. } => Some((x, y)),
+        _ => None,
+    }
+}
+
+/// how many notches the wheel moved, positive scrolls up or away from the
+/// user whichever way round the system has it set.
+pub fn wheel(event: &Event) -> Option<i32> {
+    match *event {
+        Event::MouseWheel { y, direction, .. } => match direction {
+            MouseWheelDirection::Flipped => Some(-y),
+            _ => Some(y),
+        },
+        _ => None,
+    }
+}
+
+/// a clickable area. a click is the left button going down and coming back
+/// up both inside it, so dragging off a button cancels it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Button {
+    pub bounds: Bounds,
+    hovered: bool,
+    pressed: bool,
+}
+
+impl Button {
+    pub fn new(bounds: Bounds) -> Self {
+        Self {
+            bounds,
+            hovered: false,
+            pressed: false,
+        }
+    }
+
+    /// pointer is over the button.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    /// left button went down on this button and hasn't come up yet.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// updates hover and press state from a mouse event, true if it
+    /// finished a click.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        let (x, y) = match position(event) {
+            Some(p) => p,
+            None => return false,
+        };
+        self.hovered = self.bounds.contains(x, y);
+        match *event {
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => {
+                self.pressed = self.hovered;
+                false
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => {
+                let clicked = self.pressed && self.hovered;
+                self.pressed = false;
+                clicked
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::mouse::MouseState;
+
+    fn motion(x: i32, y: i32) -> Event {
+        Event::MouseMotion {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mousestate: MouseState::from_sdl_state(0),
+            x,
+            y,
+            xrel: 0,
+            yrel: 0,
+        }
+    }
+
+    fn press(x: i32, y: i32, down: bool) -> Event {
+        if down {
+            Event::MouseButtonDown {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                mouse_btn: MouseButton::Left,
+                clicks: 1,
+                x,
+                y,
+            }
+        } else {
+            Event::MouseButtonUp {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                mouse_btn: MouseButton::Left,
+                clicks: 1,
+                x,
+                y,
+            }
+        }
+    }
+
+    fn scroll(y: i32) -> Event {
+        Event::MouseWheel {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            x: 0,
+            y,
+            direction: MouseWheelDirection::Normal,
+        }
+    }
+
+    #[test]
+    fn test_click_inside() {
+        let mut button = Button::new(Bounds::new(10, 10, 20, 20));
+        assert!(!button.handle_event(&motion(0, 0)));
+        assert!(!button.is_hovered());
+        button.handle_event(&motion(15, 15));
+        assert!(button.is_hovered());
+        assert!(!button.handle_event(&press(15, 15, true)));
+        assert!(button.is_pressed());
+        assert!(button.handle_event(&press(16, 16, false)));
+        assert!(!button.is_pressed());
+    }
+
+    #[test]
+    fn test_drag_off_cancels() {
+        let mut button = Button::new(Bounds::new(10, 10, 20, 20));
+        button.handle_event(&press(15, 15, true));
+        assert!(!button.handle_event(&press(50, 50, false)));
+        // pressing outside and releasing inside isn't a click either.
+        button.handle_event(&press(50, 50, true));
+        assert!(!button.handle_event(&press(15, 15, false)));
+    }
+
+    #[test]
+    fn test_position_and_wheel() {
+        assert_eq!(position(&motion(3, 4)), Some((3, 4)));
+        assert_eq!(position(&scroll(1)), None);
+        assert_eq!(wheel(&scroll(-2)), Some(-2));
+        assert_eq!(wheel(&motion(3, 4)), None);
+    }
+}
@@ -0,0 +1,166 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::highscore::MAX_NAME_LEN;
+use crate::layout::{stack_height, Anchor, Bounds};
+use crate::line_editor::LineEditor;
+use crate::text_cache::{FontId, TextCache};
+use crate::theme::Theme;
+use crate::widget::{DrawableWidget, EventResult, Widget};
+
+const CARET_WIDTH: u32 = 2;
+
+/// asks for a name to put against a new high score.
+pub struct NameEntry<'ttf, 'tc, 'callback> {
+    input: LineEditor,
+    score: u64,
+
+    text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+    title_font: FontId,
+    text_font: FontId,
+    theme: Theme,
+    width: u32,
+    height: u32,
+
+    /// called with the name once enter is pressed, may be empty.
+    name_callback: &'callback dyn Fn(String),
+}
+
+impl<'ttf, 'tc, 'callback> NameEntry<'ttf, 'tc, 'callback> {
+    pub fn new(
+        score: u64,
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+        name_callback: &'callback dyn Fn(String),
+    ) -> Self {
+        let title_font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.title_size)
+            .unwrap();
+        let text_font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.text_size)
+            .unwrap();
+        Self {
+            input: LineEditor::new(),
+            score,
+            text,
+            title_font,
+            text_font,
+            theme: theme.clone(),
+            width: size.0,
+            height: size.1,
+            name_callback,
+        }
+    }
+}
+
+impl<'ttf, 'tc, 'callback> Widget for NameEntry<'ttf, 'tc, 'callback> {
+    fn update_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::TextInput { text, .. } => {
+                self.input.insert_str(&text);
+                // past the limit the name would only be cut short when saved.
+                if self.input.text().chars().count() > MAX_NAME_LEN {
+                    let name: String = self.input.text().chars().take(MAX_NAME_LEN).collect();
+                    self.input.set_text(&name);
+                }
+                EventResult::Handled
+            }
+            Event::KeyDown {
+                keycode: Some(k),
+                repeat,
+                ..
+            } => {
+                match k {
+                    Keycode::Backspace => self.input.backspace(),
+                    Keycode::Delete => self.input.delete(),
+                    Keycode::Left => self.input.left(),
+                    Keycode::Right => self.input.right(),
+                    Keycode::Home => self.input.home(),
+                    Keycode::End => self.input.end(),
+                    Keycode::Return | Keycode::KpEnter if !repeat => {
+                        (self.name_callback)(self.input.take());
+                    }
+                    _ => (),
+                }
+                // escape included, the score is always kept so there's
+                // nothing to back out to.
+                EventResult::Handled
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, _: f32) {}
+
+    fn get_current_string(&self) -> String {
+        String::from(self.input.text())
+    }
+}
+
+impl<'ttf, 'tc, 'callback> DrawableWidget for NameEntry<'ttf, 'tc, 'callback> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        let screen = Bounds::new(x as i32, y as i32, self.width, self.height);
+
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(self.theme.overlay);
+        canvas
+            .fill_rect(screen.to_rect())
+            .expect("Failed to dim behind name entry");
+        canvas.set_blend_mode(BlendMode::None);
+
+        let mut text = self.text.borrow_mut();
+        let title = "New High Score!";
+        let lines = [
+            format!("Score {}", self.score),
+            String::from("Enter your name"),
+        ];
+        let title_height = text.line_height(self.title_font);
+        let line_height = text.line_height(self.text_font);
+        // title, a blank row, the two lines, another blank row and the name.
+        let heights = [2 * title_height, line_height, 2 * line_height, line_height];
+        let block = screen.anchored((self.width, stack_height(&heights, 0)), Anchor::Centre);
+        let rows = block.vstack(&heights, 0);
+
+        let title_size = (text.text_width(self.title_font, title), title_height);
+        let at = rows[0].anchored(title_size, Anchor::Top);
+        text.draw(canvas, self.title_font, title, self.theme.highlight, at.x, at.y)
+            .expect("Failed to draw name entry title");
+        for (line, row) in lines.iter().zip(rows[1..3].iter()) {
+            let at = row.anchored(
+                (text.text_width(self.text_font, line), line_height),
+                Anchor::Top,
+            );
+            text.draw(canvas, self.text_font, line, self.theme.muted, at.x, at.y)
+                .expect("Failed to draw name entry line");
+        }
+
+        // the name stays centred as it grows, the caret follows the cursor.
+        let name = self.input.text();
+        let at = rows[3].anchored(
+            (text.text_width(self.text_font, name), line_height),
+            Anchor::Top,
+        );
+        if !name.is_empty() {
+            text.draw(canvas, self.text_font, name, self.theme.text, at.x, at.y)
+                .expect("Failed to draw name");
+        }
+        let caret_x = at.x + text.text_width(self.text_font, self.input.before_cursor()) as i32;
+        canvas.set_draw_color(self.theme.text);
+        canvas
+            .fill_rect(Rect::new(caret_x, at.y, CARET_WIDTH, line_height))
+            .expect("Failed to draw caret");
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+}
@@ -0,0 +1,205 @@
+/// runs games without any window, for evaluating controllers.
+use std::time::{Duration, Instant};
+
+use crate::asteroids::{self, GameInput, GameState};
+use crate::controller::{Controller, ScriptedController};
+use crate::utils::parse_arg_value;
+
+/// time step used for every update, same as the windowed game.
+pub const TICK_DT: f64 = 0.1;
+
+/// results of a single finished (or cut off) game.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameStats {
+    pub seed: u64,
+    pub score: u64,
+    /// ticks the player stayed alive for.
+    pub ticks: u64,
+    pub shots_fired: u64,
+    pub shots_hit: u64,
+    /// true if the game ended from hitting max_ticks rather than game over.
+    pub timed_out: bool,
+}
+
+impl GameStats {
+    pub fn from_state(game_state: &GameState) -> Self {
+        Self {
+            seed: game_state.seed,
+            score: game_state.score,
+            ticks: game_state.ticks,
+            shots_fired: game_state.shots_fired,
+            shots_hit: game_state.shots_hit,
+            timed_out: !game_state.game_over,
+        }
+    }
+
+    /// fraction of fired bullets that hit something, 0 if nothing was fired.
+    pub fn accuracy(&self) -> f64 {
+        accuracy(self.shots_hit, self.shots_fired)
+    }
+}
+
+/// fraction of shots_fired that hit, 0 if nothing was fired. shared by
+/// everything that adds up games.
+pub fn accuracy(shots_hit: u64, shots_fired: u64) -> f64 {
+    mean(shots_hit, shots_fired)
+}
+
+/// total averaged over count, 0 when there's nothing to average.
+pub fn mean(total: u64, count: u64) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+/// plays a single seeded game until game over or max_ticks updates have run.
+pub fn play_game(controller: &mut dyn Controller, seed: u64, max_ticks: u64) -> GameStats {
+    let mut game_state = asteroids::game_init_seeded(seed);
+    while !game_state.game_over && game_state.ticks < max_ticks {
+        let game_input = controller.decide(&game_state);
+        asteroids::game_update(&mut game_state, TICK_DT, &game_input);
+    }
+    GameStats::from_state(&game_state)
+}
+
+/// inputs for `ticks` ticks of a bot playing seeded games back to back,
+/// recorded up front so the benchmark only times the update itself.
+fn record_inputs(seed: u64, ticks: u64) -> Vec<GameInput> {
+    let mut bot = ScriptedController::new();
+    let mut inputs = Vec::with_capacity(ticks as usize);
+    let mut game_state = asteroids::game_init_seeded(seed);
+    for _ in 0..ticks {
+        if game_state.game_over {
+            game_state = asteroids::game_init_seeded(seed);
+        }
+        let game_input = bot.decide(&game_state);
+        asteroids::game_update(&mut game_state, TICK_DT, &game_input);
+        inputs.push(game_input);
+    }
+    inputs
+}
+
+/// time taken to run the same ticks through game_update_cloned and game_update.
+pub fn bench_update(seed: u64, ticks: u64) -> (Duration, Duration) {
+    let inputs = record_inputs(seed, ticks);
+
+    let start = Instant::now();
+    let mut game_state = asteroids::game_init_seeded(seed);
+    for game_input in inputs.iter() {
+        if game_state.game_over {
+            game_state = asteroids::game_init_seeded(seed);
+        }
+        game_state = asteroids::game_update_cloned(game_state, TICK_DT, game_input);
+    }
+    let cloned = start.elapsed();
+
+    let start = Instant::now();
+    let mut game_state = asteroids::game_init_seeded(seed);
+    for game_input in inputs.iter() {
+        if game_state.game_over {
+            game_state = asteroids::game_init_seeded(seed);
+        }
+        asteroids::game_update(&mut game_state, TICK_DT, game_input);
+    }
+    let in_place = start.elapsed();
+
+    (cloned, in_place)
+}
+
+/// entry point for the bench command, args are everything after "bench".
+/// rasteroids bench [--ticks N] [--seed S]
+pub fn run_bench_from_args(args: &[String]) -> Result<(), String> {
+    let mut ticks = 1_000_000;
+    let mut seed = 0;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--ticks" => ticks = parse_arg_value(arg, iter.next())?,
+            "--seed" => seed = parse_arg_value(arg, iter.next())?,
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    let (cloned, in_place) = bench_update(seed, ticks);
+    let per_tick = |d: Duration| d.as_nanos() as f64 / ticks.max(1) as f64;
+    println!(
+        "game_update_cloned: {:.3}s {:.0} ns/tick",
+        cloned.as_secs_f64(),
+        per_tick(cloned)
+    );
+    println!(
+        "game_update:        {:.3}s {:.0} ns/tick",
+        in_place.as_secs_f64(),
+        per_tick(in_place)
+    );
+    println!(
+        "speedup: {:.2}x",
+        cloned.as_secs_f64() / in_place.as_secs_f64().max(f64::EPSILON)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroids::Asteroid;
+    use crate::controller::ReplayController;
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let first = play_game(&mut ScriptedController::new(), 42, 500);
+        let second = play_game(&mut ScriptedController::new(), 42, 500);
+        assert_eq!(first, second);
+        assert_eq!(first.seed, 42);
+    }
+
+    #[test]
+    fn test_in_place_split() {
+        // player sits at 50,50 facing along x, straight at the asteroid.
+        let mut game_state = asteroids::game_init_seeded(5);
+        game_state.asteroids = vec![Asteroid::new(61.0, 50.0, 0.0, 0.0, 8.0)];
+        let shoot = GameInput {
+            shoot: true,
+            ..Default::default()
+        };
+        asteroids::game_update(&mut game_state, TICK_DT, &shoot);
+        for _ in 0..10 {
+            asteroids::game_update(&mut game_state, TICK_DT, &GameInput::default());
+        }
+
+        assert_eq!(game_state.asteroids.len(), 2);
+        assert!(game_state.bullets().is_empty());
+        assert_eq!(game_state.score, 100);
+        assert_eq!(game_state.shots_hit, 1);
+        assert!(!game_state.game_over);
+
+        let cloned = asteroids::game_update_cloned(game_state.clone(), TICK_DT, &shoot);
+        asteroids::game_update(&mut game_state, TICK_DT, &shoot);
+        assert_eq!(
+            GameStats::from_state(&cloned),
+            GameStats::from_state(&game_state)
+        );
+    }
+
+    #[test]
+    fn test_averages() {
+        assert_eq!(mean(0, 0), 0.0);
+        assert_eq!(mean(30, 4), 7.5);
+        assert_eq!(accuracy(3, 4), 0.75);
+        assert_eq!(accuracy(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_max_ticks() {
+        // left alone this game lasts past 10 ticks, so the limit is what stops it.
+        let uncapped = play_game(&mut ReplayController::new(vec![]), 10, 1000);
+        assert!(uncapped.ticks > 10);
+        let stats = play_game(&mut ReplayController::new(vec![]), 10, 10);
+        assert_eq!(stats.ticks, 10);
+        assert_eq!(stats.shots_fired, 0);
+        assert_eq!(stats.accuracy(), 0.0);
+    }
+}
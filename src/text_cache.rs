@@ -0,0 +1,251 @@
+/// renders text once and keeps the textures around, so text that doesn't
+/// change between frames isn't rasterized again every frame.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::video::{Window, WindowContext};
+
+/// frames a rendered line can go undrawn before its texture is dropped.
+const MAX_UNUSED_FRAMES: u64 = 120;
+
+/// values tagged with the frame they were last used in, anything left
+/// unused for too long is dropped at the end of a frame.
+pub struct FrameCache<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    frame: u64,
+}
+
+impl<K: Eq + Hash, V> FrameCache<K, V> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    /// the value for key, made with make the first time it's asked for.
+    pub fn get_or_try_insert_with<F, E>(&mut self, key: K, make: F) -> Result<&V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        let frame = self.frame;
+        let entry = match self.entries.entry(key) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert((make()?, frame)),
+        };
+        entry.1 = frame;
+        Ok(&entry.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// drops everything not used in the last max_unused frames and moves
+    /// on to the next frame.
+    pub fn end_frame(&mut self, max_unused: u64) {
+        let frame = self.frame;
+        self.entries
+            .retain(|_, (_, last_used)| frame - *last_used <= max_unused);
+        self.frame += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<K: Eq + Hash, V> Default for FrameCache<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// handle to a font loaded into a TextCache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct TextKey {
+    font: FontId,
+    text: String,
+    color: Color,
+}
+
+struct CachedText<'tc> {
+    texture: Texture<'tc>,
+    width: u32,
+    height: u32,
+}
+
+struct LoadedFont<'ttf> {
+    path: PathBuf,
+    size: u16,
+    font: Font<'ttf, 'static>,
+}
+
+/// fonts plus the textures of every line recently drawn with them, keyed by
+/// font, size, text and color. shared by everything that draws text.
+pub struct TextCache<'ttf, 'tc> {
+    ttf_context: &'ttf Sdl2TtfContext,
+    texture_creator: &'tc TextureCreator<WindowContext>,
+    fonts: Vec<LoadedFont<'ttf>>,
+    lines: FrameCache<TextKey, CachedText<'tc>>,
+}
+
+impl<'ttf, 'tc> TextCache<'ttf, 'tc> {
+    pub fn new(
+        ttf_context: &'ttf Sdl2TtfContext,
+        texture_creator: &'tc TextureCreator<WindowContext>,
+    ) -> Self {
+        Self {
+            ttf_context,
+            texture_creator,
+            fonts: Vec::new(),
+            lines: FrameCache::new(),
+        }
+    }
+
+    /// loads a font at a point size, a font already loaded at that size is reused.
+    pub fn load_font(&mut self, path: &Path, size: u16) -> Result<FontId, String> {
+        if let Some(index) = self
+            .fonts
+            .iter()
+            .position(|f| f.path == path && f.size == size)
+        {
+            return Ok(FontId(index));
+        }
+        let font = self.ttf_context.load_font(path, size)?;
+        self.fonts.push(LoadedFont {
+            path: path.to_path_buf(),
+            size,
+            font,
+        });
+        Ok(FontId(self.fonts.len() - 1))
+    }
+
+    pub fn font(&self, font: FontId) -> &Font<'ttf, 'static> {
+        &self.fonts[font.0].font
+    }
+
+    /// rendered width of text in pixels.
+    pub fn text_width(&self, font: FontId, text: &str) -> u32 {
+        self.font(font).size_of(text).map_or(0, |(w, _)| w)
+    }
+
+    /// distance in pixels between the tops of two lines of text.
+    pub fn line_height(&self, font: FontId) -> u32 {
+        self.font(font).recommended_line_spacing().max(1) as u32
+    }
+
+    /// draws text at its natural size with its top left at x, y and returns
+    /// that size. only rasterized the first time it's drawn.
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        font: FontId,
+        text: &str,
+        color: Color,
+        x: i32,
+        y: i32,
+    ) -> Result<(u32, u32), String> {
+        if text.is_empty() {
+            return Ok((0, self.line_height(font)));
+        }
+        let key = TextKey {
+            font,
+            text: String::from(text),
+            color,
+        };
+        let fonts = &self.fonts;
+        let texture_creator = self.texture_creator;
+        let cached = self.lines.get_or_try_insert_with(key, || {
+            let surface = fonts[font.0]
+                .font
+                .render(text)
+                .blended(color)
+                .map_err(|e| e.to_string())?;
+            let texture = texture_creator
+                .create_texture_from_surface(&surface)
+                .map_err(|e| e.to_string())?;
+            Ok::<_, String>(CachedText {
+                texture,
+                width: surface.width(),
+                height: surface.height(),
+            })
+        })?;
+        canvas.copy(
+            &cached.texture,
+            None,
+            Some(Rect::new(x, y, cached.width, cached.height)),
+        )?;
+        Ok((cached.width, cached.height))
+    }
+
+    /// call once per presented frame, lets go of lines that stopped being drawn.
+    pub fn end_frame(&mut self) {
+        self.lines.end_frame(MAX_UNUSED_FRAMES);
+    }
+
+    pub fn cached_lines(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_made_once() {
+        let mut cache = FrameCache::new();
+        let mut made = 0;
+        for _ in 0..3 {
+            let value = cache
+                .get_or_try_insert_with("score", || {
+                    made += 1;
+                    Ok::<_, ()>(made * 10)
+                })
+                .unwrap();
+            assert_eq!(*value, 10);
+            cache.end_frame(5);
+        }
+        assert_eq!(made, 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_unused_dropped() {
+        let mut cache = FrameCache::new();
+        cache
+            .get_or_try_insert_with("old", || Ok::<_, ()>(1))
+            .unwrap();
+        cache.end_frame(2);
+        for _ in 0..3 {
+            cache
+                .get_or_try_insert_with("new", || Ok::<_, ()>(2))
+                .unwrap();
+            cache.end_frame(2);
+        }
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get_or_try_insert_with("new", || Err(())), Ok(&2));
+        assert_eq!(cache.get_or_try_insert_with("old", || Err(())), Err(()));
+    }
+
+    #[test]
+    fn test_failed_make_not_cached() {
+        let mut cache: FrameCache<&str, i32> = FrameCache::new();
+        assert!(cache.get_or_try_insert_with("x", || Err("bad")).is_err());
+        assert!(cache.is_empty());
+    }
+}
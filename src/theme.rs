@@ -0,0 +1,258 @@
+/// colours, font and text sizes shared by every widget, picked from a few
+/// built in themes or loaded from a file.
+use sdl2::pixels::Color;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// loaded at startup if it exists, e.g. "base dark" and a few overrides.
+pub const THEME_FILE: &str = "theme.cfg";
+pub const BUILTIN_THEMES: &[&str] = &["classic", "dark", "high_contrast"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// relative paths are looked up next to the game.
+    pub font: PathBuf,
+    /// menu and screen titles.
+    pub title_size: u16,
+    /// menu items and most other text.
+    pub text_size: u16,
+    /// hud and toasts.
+    pub hud_size: u16,
+    /// console and debug panels.
+    pub small_size: u16,
+
+    /// behind the game world.
+    pub background: Color,
+    pub text: Color,
+    /// less important text, e.g. menu items that aren't selected.
+    pub muted: Color,
+    /// whatever is selected.
+    pub highlight: Color,
+    pub error: Color,
+    pub warning: Color,
+    pub debug: Color,
+    /// dims the game behind menus.
+    pub overlay: Color,
+    /// behind toasts and debug panels.
+    pub panel: Color,
+    pub console_text: Color,
+    pub console_background: Color,
+}
+
+impl Theme {
+    /// the look the game has always had.
+    pub fn classic() -> Self {
+        Self {
+            font: PathBuf::from("lazy.ttf"),
+            title_size: 48,
+            text_size: 24,
+            hud_size: 20,
+            small_size: 18,
+            background: Color::RGBA(0, 0, 0, 255),
+            text: Color::RGBA(255, 255, 255, 255),
+            muted: Color::RGBA(170, 170, 170, 255),
+            highlight: Color::RGBA(255, 200, 0, 255),
+            error: Color::RGBA(255, 255, 255, 255),
+            warning: Color::RGBA(255, 140, 0, 255),
+            debug: Color::RGBA(70, 70, 70, 255),
+            overlay: Color::RGBA(0, 0, 0, 180),
+            panel: Color::RGBA(20, 20, 30, 200),
+            console_text: Color::RGBA(255, 0, 0, 255),
+            console_background: Color::RGBA(0, 200, 0, 255),
+        }
+    }
+
+    /// muted greys and blues that are easier on the eyes.
+    pub fn dark() -> Self {
+        Self {
+            muted: Color::RGBA(118, 131, 144, 255),
+            highlight: Color::RGBA(83, 155, 245, 255),
+            error: Color::RGBA(229, 83, 75, 255),
+            warning: Color::RGBA(198, 144, 38, 255),
+            debug: Color::RGBA(99, 110, 123, 255),
+            panel: Color::RGBA(34, 39, 46, 220),
+            console_text: Color::RGBA(173, 186, 199, 255),
+            console_background: Color::RGBA(34, 39, 46, 255),
+            ..Self::classic()
+        }
+    }
+
+    /// pure black and white with bigger text, nothing see through.
+    pub fn high_contrast() -> Self {
+        Self {
+            title_size: 56,
+            text_size: 30,
+            hud_size: 26,
+            small_size: 22,
+            muted: Color::RGBA(255, 255, 255, 255),
+            highlight: Color::RGBA(255, 255, 0, 255),
+            error: Color::RGBA(255, 96, 96, 255),
+            warning: Color::RGBA(255, 170, 0, 255),
+            debug: Color::RGBA(0, 255, 255, 255),
+            overlay: Color::RGBA(0, 0, 0, 235),
+            panel: Color::RGBA(0, 0, 0, 255),
+            console_text: Color::RGBA(255, 255, 255, 255),
+            console_background: Color::RGBA(0, 0, 0, 255),
+            ..Self::classic()
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::classic()),
+            "dark" => Some(Self::dark()),
+            "high_contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// reads a theme script, one "key value" per line. "base <theme>" resets
+    /// to a built in theme so goes first, anything not set keeps its classic
+    /// value.
+    /// lines that don't make sense are skipped and returned as errors.
+    pub fn from_script(script: &str) -> (Self, Vec<String>) {
+        let mut theme = Self::classic();
+        let mut errors = Vec::new();
+        for (line_number, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.find(char::is_whitespace) {
+                Some(i) => (&line[..i], line[i..].trim()),
+                None => (line, ""),
+            };
+            if let Err(e) = theme.set(key, value) {
+                errors.push(format!("line {}: {}", line_number + 1, e));
+            }
+        }
+        (theme, errors)
+    }
+
+    pub fn load(path: &Path) -> Result<(Self, Vec<String>), String> {
+        let script = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Ok(Self::from_script(&script))
+    }
+
+    /// a built in theme by name, otherwise a theme file at that path.
+    pub fn from_spec(spec: &str) -> Result<(Self, Vec<String>), String> {
+        match Self::builtin(spec) {
+            Some(theme) => Ok((theme, Vec::new())),
+            None => Self::load(Path::new(spec)),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let size = |value: &str| {
+            value
+                .parse::<u16>()
+                .ok()
+                .filter(|s| *s > 0)
+                .ok_or_else(|| format!("invalid size '{}' for {}", value, key))
+        };
+        match key {
+            "base" => {
+                *self = Self::builtin(value).ok_or_else(|| format!("unknown theme '{}'", value))?
+            }
+            "font" if value.is_empty() => return Err(String::from("font needs a path")),
+            "font" => self.font = PathBuf::from(value),
+            "title_size" => self.title_size = size(value)?,
+            "text_size" => self.text_size = size(value)?,
+            "hud_size" => self.hud_size = size(value)?,
+            "small_size" => self.small_size = size(value)?,
+            _ => {
+                let color = self.color_mut(key)?;
+                *color = parse_color(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn color_mut(&mut self, key: &str) -> Result<&mut Color, String> {
+        Ok(match key {
+            "background" => &mut self.background,
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "highlight" => &mut self.highlight,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "debug" => &mut self.debug,
+            "overlay" => &mut self.overlay,
+            "panel" => &mut self.panel,
+            "console_text" => &mut self.console_text,
+            "console_background" => &mut self.console_background,
+            _ => return Err(format!("unknown theme setting '{}'", key)),
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+/// "#rrggbb", or "#rrggbbaa" for something see through.
+pub fn parse_color(text: &str) -> Result<Color, String> {
+    let invalid = || format!("invalid color '{}', expected #rrggbb or #rrggbbaa", text);
+    let hex = text.strip_prefix('#').ok_or_else(invalid)?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Ok(Color::RGBA(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8000"), Ok(Color::RGBA(255, 128, 0, 255)));
+        assert_eq!(parse_color("#000000b4"), Ok(Color::RGBA(0, 0, 0, 180)));
+        assert!(parse_color("ff8000").is_err());
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg8000").is_err());
+    }
+
+    #[test]
+    fn test_builtins() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::builtin(name).is_some(), "{}", name);
+        }
+        assert!(Theme::builtin("neon").is_none());
+        let high_contrast = Theme::high_contrast();
+        assert!(high_contrast.text_size > Theme::classic().text_size);
+        assert_eq!(high_contrast.panel.a, 255);
+    }
+
+    #[test]
+    fn test_script() {
+        let script = "# my theme\nbase dark\nfont fonts/other.ttf\n\n\
+                      highlight #00ff00\ntext_size 30\nsparkles on\nhud_size 0\n";
+        let (theme, errors) = Theme::from_script(script);
+        assert_eq!(
+            errors,
+            vec![
+                "line 7: unknown theme setting 'sparkles'",
+                "line 8: invalid size '0' for hud_size",
+            ]
+        );
+        assert_eq!(theme.font, PathBuf::from("fonts/other.ttf"));
+        assert_eq!(theme.highlight, Color::RGBA(0, 255, 0, 255));
+        assert_eq!(theme.text_size, 30);
+        assert_eq!(theme.console_background, Theme::dark().console_background);
+        assert_eq!(theme.hud_size, Theme::dark().hud_size);
+    }
+
+    #[test]
+    fn test_from_spec() {
+        let (theme, errors) = Theme::from_spec("high_contrast").unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(theme, Theme::high_contrast());
+        assert!(Theme::from_spec("no/such/theme.cfg").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use sdl2::event::Event;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::asteroids::GameState;
+use crate::layout::{Anchor, Bounds, Padding};
+use crate::text_cache::{FontId, TextCache};
+use crate::theme::Theme;
+use crate::tween::{Easing, Tween};
+use crate::widget::{DrawableWidget, EventResult, Widget};
+
+/// seconds a toast takes to slide in, and again to slide out.
+const SLIDE_SECONDS: f32 = 0.25;
+/// seconds a toast stays put once it's in.
+const HOLD_SECONDS: f32 = 2.0;
+/// messages waiting past this are dropped oldest first.
+const MAX_WAITING: usize = 8;
+/// how far down from the top of the window toasts settle, below the hud.
+const TOP_OFFSET: i32 = 48;
+const PADDING: u32 = 8;
+
+struct Showing {
+    message: String,
+    /// 0 off screen to 1 fully in.
+    slide: Tween,
+    /// seconds since it finished sliding in.
+    held: f32,
+    leaving: bool,
+}
+
+/// messages shown one at a time, each sliding in, staying a moment and
+/// sliding back out before the next one.
+#[derive(Default)]
+pub struct ToastQueue {
+    waiting: VecDeque<String>,
+    showing: Option<Showing>,
+}
+
+impl ToastQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<S: Into<String>>(&mut self, message: S) {
+        self.waiting.push_back(message.into());
+        if self.waiting.len() > MAX_WAITING {
+            self.waiting.pop_front();
+        }
+    }
+
+    /// number of messages not yet shown, not counting the one showing.
+    pub fn waiting(&self) -> usize {
+        self.waiting.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.showing.is_none() && self.waiting.is_empty()
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        let showing = match self.showing.as_mut() {
+            Some(showing) => showing,
+            None => {
+                if let Some(message) = self.waiting.pop_front() {
+                    self.showing = Some(Showing {
+                        message,
+                        slide: Tween::new(0.0, 1.0, SLIDE_SECONDS, Easing::EaseOut),
+                        held: 0.0,
+                        leaving: false,
+                    });
+                }
+                return;
+            }
+        };
+        showing.slide.update(dt);
+        if !showing.slide.finished() {
+            return;
+        }
+        if showing.leaving {
+            self.showing = None;
+            return;
+        }
+        showing.held += dt;
+        if showing.held >= HOLD_SECONDS {
+            showing.leaving = true;
+            showing.slide = Tween::new(1.0, 0.0, SLIDE_SECONDS, Easing::EaseIn);
+        }
+    }
+
+    /// message on screen and how far in it is, 0 to 1.
+    pub fn current(&self) -> Option<(&str, f32)> {
+        self.showing
+            .as_ref()
+            .map(|s| (s.message.as_str(), s.slide.value()))
+    }
+}
+
+/// watches the game between frames for things worth a toast.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GameWatch {
+    wave: u32,
+    lives: u32,
+    ticks: u64,
+}
+
+impl GameWatch {
+    pub fn new(game_state: &GameState) -> Self {
+        Self {
+            wave: game_state.wave,
+            lives: game_state.lives,
+            ticks: game_state.ticks,
+        }
+    }
+
+    /// messages for whatever changed since last time.
+    pub fn changes(&mut self, game_state: &GameState) -> Vec<String> {
+        let last = std::mem::replace(self, Self::new(game_state));
+        // a new game isn't news, it just starts the watch over.
+        if game_state.ticks < last.ticks {
+            return Vec::new();
+        }
+        let mut messages = Vec::new();
+        if game_state.wave > last.wave {
+            messages.push(format!("Wave {}", game_state.wave));
+        }
+        if game_state.lives > last.lives {
+            messages.push(String::from("Extra life!"));
+        } else if game_state.lives < last.lives && !game_state.game_over {
+            messages.push(format!("Ship lost, {} left", game_state.lives));
+        }
+        messages
+    }
+}
+
+/// draws the toast queue over the game. never has focus so it isn't kept
+/// on the widget stack, where escape would close it.
+pub struct Toasts<'ttf, 'tc> {
+    pub queue: ToastQueue,
+
+    text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+    font: FontId,
+    theme: Theme,
+    width: u32,
+    height: u32,
+}
+
+impl<'ttf, 'tc> Toasts<'ttf, 'tc> {
+    pub fn new(
+        text: Rc<RefCell<TextCache<'ttf, 'tc>>>,
+        theme: &Theme,
+        size: (u32, u32),
+    ) -> Self {
+        let font = text
+            .borrow_mut()
+            .load_font(&theme.font, theme.hud_size)
+            .unwrap();
+        Self {
+            queue: ToastQueue::new(),
+            text,
+            font,
+            theme: theme.clone(),
+            width: size.0,
+            height: size.1,
+        }
+    }
+}
+
+impl<'ttf, 'tc> Widget for Toasts<'ttf, 'tc> {
+    fn update_event(&mut self, _: Event) -> EventResult {
+        EventResult::Ignored
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.queue.update(dt);
+    }
+
+    fn get_current_string(&self) -> String {
+        self.queue
+            .current()
+            .map_or_else(String::new, |(message, _)| String::from(message))
+    }
+}
+
+impl<'ttf, 'tc> DrawableWidget for Toasts<'ttf, 'tc> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        let (message, shown) = match self.queue.current() {
+            Some(current) => current,
+            None => return,
+        };
+        let mut text = self.text.borrow_mut();
+        let size = (
+            text.text_width(self.font, message) + 2 * PADDING,
+            text.line_height(self.font) + 2 * PADDING,
+        );
+        // slides down from just above the window to its resting place.
+        let screen = Bounds::new(x as i32, y as i32, self.width, self.height);
+        let rest = screen.anchored(size, Anchor::Top);
+        let travel = (TOP_OFFSET + size.1 as i32) as f32;
+        let box_y = rest.y - size.1 as i32 + (travel * shown).round() as i32;
+        let frame = Bounds { y: box_y, ..rest };
+
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(self.theme.panel);
+        canvas
+            .fill_rect(frame.to_rect())
+            .expect("Failed to draw toast background");
+        canvas.set_blend_mode(BlendMode::None);
+
+        let inner = frame.inset(Padding::all(PADDING));
+        text.draw(canvas, self.font, message, self.theme.text, inner.x, inner.y)
+            .expect("Failed to draw toast");
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroids::game_init_seeded;
+
+    #[test]
+    fn test_toast_lifecycle() {
+        let mut queue = ToastQueue::new();
+        assert!(queue.current().is_none());
+        queue.push("first");
+        queue.push("second");
+
+        queue.update(0.0);
+        assert_eq!(queue.current(), Some(("first", 0.0)));
+        assert_eq!(queue.waiting(), 1);
+        queue.update(SLIDE_SECONDS);
+        assert_eq!(queue.current(), Some(("first", 1.0)));
+
+        // stays in for the hold, then slides out.
+        queue.update(HOLD_SECONDS / 2.0);
+        assert_eq!(queue.current(), Some(("first", 1.0)));
+        queue.update(HOLD_SECONDS / 2.0);
+        queue.update(SLIDE_SECONDS / 2.0);
+        let (_, shown) = queue.current().unwrap();
+        assert!(shown > 0.0 && shown < 1.0);
+        queue.update(SLIDE_SECONDS / 2.0);
+        assert!(queue.current().is_none());
+
+        queue.update(0.0);
+        assert_eq!(queue.current().map(|(m, _)| m), Some("second"));
+        for _ in 0..100 {
+            queue.update(0.1);
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_waiting_capped() {
+        let mut queue = ToastQueue::new();
+        for i in 0..MAX_WAITING + 3 {
+            queue.push(format!("{}", i));
+        }
+        assert_eq!(queue.waiting(), MAX_WAITING);
+        queue.update(0.0);
+        assert_eq!(queue.current().map(|(m, _)| m), Some("3"));
+    }
+
+    #[test]
+    fn test_game_watch() {
+        let mut game_state = game_init_seeded(3);
+        let mut watch = GameWatch::new(&game_state);
+        assert!(watch.changes(&game_state).is_empty());
+
+        game_state.ticks += 10;
+        game_state.wave += 1;
+        game_state.lives += 1;
+        assert_eq!(watch.changes(&game_state), vec!["Wave 2", "Extra life!"]);
+        game_state.lives -= 1;
+        assert_eq!(
+            watch.changes(&game_state),
+            vec![format!("Ship lost, {} left", game_state.lives)]
+        );
+
+        let restarted = game_init_seeded(4);
+        assert!(watch.changes(&restarted).is_empty());
+    }
+}
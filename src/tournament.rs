@@ -0,0 +1,233 @@
+/// headless tournament, every controller plays the same seeded games and
+/// gets ranked on how it did.
+///
+/// rasteroids tournament [--games N] [--seed S] [--max-ticks T] [--csv FILE] <controller>...
+use std::fs;
+
+use crate::controller;
+use crate::simulation::{self, GameStats};
+use crate::utils::parse_arg_value;
+
+/// totals for one controller across all of its games.
+#[derive(Clone, Debug, Default)]
+pub struct Standing {
+    /// controller spec the standing is for.
+    pub name: String,
+    pub games: u32,
+    pub total_score: u64,
+    pub best_score: u64,
+    pub total_ticks: u64,
+    pub shots_fired: u64,
+    pub shots_hit: u64,
+}
+
+impl Standing {
+    fn add(&mut self, stats: &GameStats) {
+        self.games += 1;
+        self.total_score += stats.score;
+        self.best_score = self.best_score.max(stats.score);
+        self.total_ticks += stats.ticks;
+        self.shots_fired += stats.shots_fired;
+        self.shots_hit += stats.shots_hit;
+    }
+
+    pub fn mean_score(&self) -> f64 {
+        simulation::mean(self.total_score, self.games as u64)
+    }
+
+    /// average number of ticks survived per game.
+    pub fn mean_survival(&self) -> f64 {
+        simulation::mean(self.total_ticks, self.games as u64)
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        simulation::accuracy(self.shots_hit, self.shots_fired)
+    }
+}
+
+/// plays `games` games per controller, game i uses seed base_seed + i for
+/// everyone. returns the standings best first.
+pub fn run_tournament(
+    specs: &[String],
+    games: u32,
+    base_seed: u64,
+    max_ticks: u64,
+) -> Result<Vec<Standing>, String> {
+    let mut standings = Vec::new();
+    for spec in specs {
+        let mut standing = Standing {
+            name: spec.clone(),
+            ..Default::default()
+        };
+        for game in 0..games {
+            // fresh controller each game so replays and bots start over.
+            let mut player = controller::from_spec(spec)?;
+            let seed = base_seed.wrapping_add(game as u64);
+            let stats = simulation::play_game(player.as_mut(), seed, max_ticks);
+            standing.add(&stats);
+        }
+        standings.push(standing);
+    }
+
+    rank(&mut standings);
+    Ok(standings)
+}
+
+/// sorts best first, by mean score then by how long they survived.
+fn rank(standings: &mut [Standing]) {
+    standings.sort_by(|a, b| {
+        b.mean_score()
+            .partial_cmp(&a.mean_score())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(
+                b.mean_survival()
+                    .partial_cmp(&a.mean_survival())
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+    });
+}
+
+/// human readable ranking.
+pub fn format_table(standings: &[Standing]) -> String {
+    let name_width = standings
+        .iter()
+        .map(|s| s.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("controller".len());
+
+    let mut out = format!(
+        "{:<4} {:<width$} {:>6} {:>10} {:>10} {:>10} {:>8}\n",
+        "rank",
+        "controller",
+        "games",
+        "mean",
+        "best",
+        "survival",
+        "accuracy",
+        width = name_width
+    );
+    for (rank, s) in standings.iter().enumerate() {
+        out += &format!(
+            "{:<4} {:<width$} {:>6} {:>10.1} {:>10} {:>10.1} {:>7.1}%\n",
+            rank + 1,
+            s.name,
+            s.games,
+            s.mean_score(),
+            s.best_score,
+            s.mean_survival(),
+            s.accuracy() * 100.0,
+            width = name_width
+        );
+    }
+    out
+}
+
+/// same ranking as format_table, as csv with a header row.
+pub fn format_csv(standings: &[Standing]) -> String {
+    let mut out =
+        String::from("rank,controller,games,mean_score,best_score,mean_survival_ticks,accuracy\n");
+    for (rank, s) in standings.iter().enumerate() {
+        out += &format!(
+            "{},\"{}\",{},{},{},{},{}\n",
+            rank + 1,
+            s.name.replace('"', "\"\""),
+            s.games,
+            s.mean_score(),
+            s.best_score,
+            s.mean_survival(),
+            s.accuracy()
+        );
+    }
+    out
+}
+
+/// entry point for the tournament command, args are everything after "tournament".
+pub fn run_from_args(args: &[String]) -> Result<(), String> {
+    let mut games = 10;
+    let mut seed = 0;
+    let mut max_ticks = 10_000;
+    let mut csv_path = None;
+    let mut specs = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--games" => games = parse_arg_value(arg, iter.next())?,
+            "--seed" => seed = parse_arg_value(arg, iter.next())?,
+            "--max-ticks" => max_ticks = parse_arg_value(arg, iter.next())?,
+            "--csv" => csv_path = Some(parse_arg_value::<String>(arg, iter.next())?),
+            _ if arg.starts_with("--") => return Err(format!("unknown argument: {}", arg)),
+            _ => specs.push(arg.clone()),
+        }
+    }
+
+    if specs.is_empty() {
+        return Err(String::from(
+            "usage: rasteroids tournament [--games N] [--seed S] [--max-ticks T] [--csv FILE] <controller>...",
+        ));
+    }
+    if specs.iter().any(|s| s == "keyboard") {
+        return Err(String::from("keyboard can't play in a headless tournament"));
+    }
+
+    let standings = run_tournament(&specs, games, seed, max_ticks)?;
+    print!("{}", format_table(&standings));
+
+    if let Some(path) = csv_path {
+        fs::write(&path, format_csv(&standings))
+            .map_err(|e| format!("failed to write {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranking() {
+        let specs = vec![String::from("bot")];
+        let standings = run_tournament(&specs, 3, 7, 200).unwrap();
+        assert_eq!(standings.len(), 1);
+        assert_eq!(standings[0].games, 3);
+        assert!(standings[0].mean_survival() <= 200.0);
+
+        let csv = format_csv(&standings);
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.lines().nth(1).unwrap().starts_with("1,\"bot\",3,"));
+    }
+
+    #[test]
+    fn test_standings_sorted_best_first() {
+        let standing = |name: &str, score, ticks| {
+            let mut s = Standing {
+                name: String::from(name),
+                ..Default::default()
+            };
+            s.add(&GameStats {
+                score,
+                ticks,
+                ..Default::default()
+            });
+            s
+        };
+        let mut standings = vec![
+            standing("low", 100, 50),
+            standing("high", 500, 10),
+            standing("tied_longer", 100, 80),
+        ];
+        rank(&mut standings);
+        let names: Vec<&str> = standings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["high", "tied_longer", "low"]);
+        assert_eq!(format_table(&standings).lines().count(), 4);
+    }
+
+    #[test]
+    fn test_bad_args() {
+        assert!(run_from_args(&[]).is_err());
+        assert!(run_from_args(&[String::from("--games")]).is_err());
+        assert!(run_from_args(&[String::from("keyboard")]).is_err());
+        assert!(run_from_args(&[String::from("--bogus"), String::from("bot")]).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+/// easing curves and tweens, for animating widgets from their update(dt).
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// starts slow and speeds up.
+    EaseIn,
+    /// starts fast and slows to a stop.
+    EaseOut,
+    EaseInOut,
+    /// overshoots the end and bounces back to rest on it.
+    Bounce,
+}
+
+impl Easing {
+    /// maps progress t, 0 to 1, onto the curve. 0 and 1 map to themselves.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Bounce => bounce_out(t),
+        }
+    }
+}
+
+/// the usual four bounce ease out, a ball dropped onto the end value.
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// a value moving from one number to another over a set time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    /// seconds the whole move takes.
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, duration: f32, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+            easing,
+        }
+    }
+
+    /// a tween that's already sat at value.
+    pub fn fixed(value: f32) -> Self {
+        Self::new(value, value, 0.0, Easing::Linear)
+    }
+
+    /// moves the tween on by dt seconds.
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt.max(0.0)).min(self.duration);
+    }
+
+    /// fraction of the duration gone, 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            self.elapsed / self.duration
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.from + (self.to - self.from) * self.easing.apply(self.progress())
+    }
+
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+
+    pub fn finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// heads off towards a new value from wherever the tween is right now,
+    /// so changing direction part way through doesn't jump.
+    pub fn retarget(&mut self, to: f32, duration: f32) {
+        *self = Self::new(self.value(), to, duration, self.easing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Bounce,
+    ];
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_easing_end_points() {
+        for easing in ALL.iter() {
+            assert!(close(easing.apply(0.0), 0.0), "{:?}", easing);
+            assert!(close(easing.apply(1.0), 1.0), "{:?}", easing);
+            assert!(close(easing.apply(2.0), 1.0), "{:?}", easing);
+            assert!(close(easing.apply(-1.0), 0.0), "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn test_easing_shapes() {
+        assert!(close(Easing::Linear.apply(0.25), 0.25));
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert!(close(Easing::EaseInOut.apply(0.5), 0.5));
+        // bounce touches the end early on and comes back off it.
+        assert!(close(Easing::Bounce.apply(1.0 / 2.75), 1.0));
+        assert!(Easing::Bounce.apply(0.5) < 1.0);
+    }
+
+    #[test]
+    fn test_tween_update() {
+        let mut tween = Tween::new(-100.0, 0.0, 0.5, Easing::Linear);
+        assert_eq!(tween.value(), -100.0);
+        tween.update(0.25);
+        assert!(close(tween.value(), -50.0));
+        assert!(!tween.finished());
+        tween.update(10.0);
+        assert_eq!(tween.value(), 0.0);
+        assert!(tween.finished());
+        assert!(Tween::fixed(3.0).finished());
+        assert_eq!(Tween::fixed(3.0).value(), 3.0);
+    }
+
+    #[test]
+    fn test_retarget_from_current() {
+        let mut tween = Tween::new(0.0, 10.0, 1.0, Easing::Linear);
+        tween.update(0.5);
+        tween.retarget(0.0, 1.0);
+        assert!(close(tween.value(), 5.0));
+        assert_eq!(tween.target(), 0.0);
+        tween.update(1.0);
+        assert_eq!(tween.value(), 0.0);
+    }
+}
@@ -111,6 +111,51 @@ pub fn spaced_internals(space: u32, count: u32) -> Vec<(u32, u32)> {
     res
 }
 
+/// parses the value following a command line flag.
+pub fn parse_arg_value<T>(flag: &str, value: Option<&String>) -> Result<T, String>
+where
+    T: std::str::FromStr,
+{
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: '{}'", flag, value))
+}
+
+/// greedy word wrap of text into lines no wider than max_width, as measured
+/// by measure. words wider than a whole line are broken between characters.
+pub fn wrap_text<F>(text: &str, max_width: u32, measure: F) -> Vec<String>
+where
+    F: Fn(&str) -> u32,
+{
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            String::from(word)
+        } else {
+            format!("{} {}", line, word)
+        };
+        if measure(&candidate) <= max_width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if measure(&line) > max_width && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::take(&mut line));
+                line.push(c);
+            }
+        }
+    }
+    lines.push(line);
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +230,25 @@ mod tests {
     fn manhat_dist() {
         assert_eq!(2, manhat_distance(0, 0, 1, 1));
     }
+
+    #[test]
+    fn test_wrap_text() {
+        // every char is 10 wide.
+        let measure = |s: &str| s.chars().count() as u32 * 10;
+        assert_eq!(wrap_text("", 50, measure), vec![""]);
+        assert_eq!(
+            wrap_text("set thrust 4", 200, measure),
+            vec!["set thrust 4"]
+        );
+        assert_eq!(
+            wrap_text("give lives 10 now", 100, measure),
+            vec!["give lives", "10 now"]
+        );
+        assert_eq!(
+            wrap_text("a abcdefgh", 40, measure),
+            vec!["a", "abcd", "efgh"]
+        );
+        assert_eq!(wrap_text("wave  2", 100, measure), vec!["wave  2"]);
+        assert_eq!(wrap_text("xyz", 5, measure), vec!["x", "y", "z"]);
+    }
 }
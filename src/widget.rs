@@ -1,9 +1,22 @@
 use sdl2::event::Event;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// what a widget did with an event it was given.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventResult {
+    /// used up, nothing below the widget sees it.
+    Handled,
+    /// not for this widget, passed on down the stack.
+    Ignored,
+    /// the widget is done and wants to be taken off the stack.
+    Close,
+}
 
 pub trait Widget {
-    fn update_event(&mut self, event: Event);
+    fn update_event(&mut self, event: Event) -> EventResult;
     fn update(&mut self, dt: f32);
 
     // tmp.
@@ -12,4 +25,33 @@ pub trait Widget {
 
 pub trait DrawableWidget: Widget {
     fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32);
+
+    /// called when the window changes size, so the widget can lay itself
+    /// out again.
+    fn resize(&mut self, _width: u32, _height: u32) {}
+}
+
+// lets a widget sit on the widget stack while main keeps a handle to it.
+impl<T: Widget + ?Sized> Widget for Rc<RefCell<T>> {
+    fn update_event(&mut self, event: Event) -> EventResult {
+        self.borrow_mut().update_event(event)
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.borrow_mut().update(dt)
+    }
+
+    fn get_current_string(&self) -> String {
+        self.borrow().get_current_string()
+    }
+}
+
+impl<T: DrawableWidget + ?Sized> DrawableWidget for Rc<RefCell<T>> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>, x: u32, y: u32) {
+        self.borrow_mut().draw(canvas, x, y)
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.borrow_mut().resize(width, height)
+    }
 }
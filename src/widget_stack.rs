@@ -0,0 +1,240 @@
+/// widgets layered on top of the game. the top widget has focus, events it
+/// doesn't want fall through to the ones below and escape backs out of it.
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::widget::{DrawableWidget, EventResult};
+
+/// handle to a widget on the stack, stays valid until that widget is removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WidgetId(u64);
+
+struct Layer<'a> {
+    id: WidgetId,
+    widget: Box<dyn DrawableWidget + 'a>,
+    x: u32,
+    y: u32,
+}
+
+pub struct WidgetStack<'a> {
+    /// bottom first, the last layer has focus.
+    layers: Vec<Layer<'a>>,
+    next_id: u64,
+}
+
+impl<'a> WidgetStack<'a> {
+    pub fn new() -> Self {
+        Self {
+            layers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// puts a widget on top, drawn at x, y, and gives it focus.
+    pub fn push(&mut self, widget: Box<dyn DrawableWidget + 'a>, x: u32, y: u32) -> WidgetId {
+        let id = WidgetId(self.next_id);
+        self.next_id += 1;
+        self.layers.push(Layer { id, widget, x, y });
+        id
+    }
+
+    /// takes the top widget off, focus goes to the one below.
+    pub fn pop(&mut self) -> Option<Box<dyn DrawableWidget + 'a>> {
+        self.layers.pop().map(|l| l.widget)
+    }
+
+    /// takes a widget off wherever it is in the stack.
+    pub fn remove(&mut self, id: WidgetId) -> Option<Box<dyn DrawableWidget + 'a>> {
+        let index = self.layers.iter().position(|l| l.id == id)?;
+        Some(self.layers.remove(index).widget)
+    }
+
+    pub fn contains(&self, id: WidgetId) -> bool {
+        self.layers.iter().any(|l| l.id == id)
+    }
+
+    /// id of the widget with focus.
+    pub fn top(&self) -> Option<WidgetId> {
+        self.layers.last().map(|l| l.id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// offers event to each widget from the top down until one handles it.
+    /// escape that the top widget ignores backs out of it. false if nothing
+    /// used the event, so the game can have it.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        for index in (0..self.layers.len()).rev() {
+            match self.layers[index].widget.update_event(event.clone()) {
+                EventResult::Handled => return true,
+                EventResult::Close => {
+                    self.layers.remove(index);
+                    return true;
+                }
+                EventResult::Ignored if index + 1 == self.layers.len() && is_back_out(event) => {
+                    self.layers.pop();
+                    return true;
+                }
+                EventResult::Ignored => (),
+            }
+        }
+        false
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for layer in self.layers.iter_mut() {
+            layer.widget.update(dt);
+        }
+    }
+
+    /// tells every widget the window is now width by height.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        for layer in self.layers.iter_mut() {
+            layer.widget.resize(width, height);
+        }
+    }
+
+    /// draws bottom to top so the focused widget ends up in front.
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>) {
+        for layer in self.layers.iter_mut() {
+            layer.widget.draw(canvas, layer.x, layer.y);
+        }
+    }
+}
+
+impl<'a> Default for WidgetStack<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_back_out(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::KeyDown {
+            keycode: Some(Keycode::Escape),
+            repeat: false,
+            ..
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Widget;
+    use sdl2::keyboard::Mod;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// handles a single key, closes on another and records what it was sent.
+    struct KeyWidget {
+        handles: Keycode,
+        closes_on: Keycode,
+        seen: Rc<RefCell<Vec<Keycode>>>,
+    }
+
+    impl Widget for KeyWidget {
+        fn update_event(&mut self, event: Event) -> EventResult {
+            match event {
+                Event::KeyDown {
+                    keycode: Some(k), ..
+                } => {
+                    self.seen.borrow_mut().push(k);
+                    if k == self.handles {
+                        EventResult::Handled
+                    } else if k == self.closes_on {
+                        EventResult::Close
+                    } else {
+                        EventResult::Ignored
+                    }
+                }
+                _ => EventResult::Ignored,
+            }
+        }
+
+        fn update(&mut self, _: f32) {}
+
+        fn get_current_string(&self) -> String {
+            String::new()
+        }
+    }
+
+    impl DrawableWidget for KeyWidget {
+        fn draw(&mut self, _: &mut Canvas<Window>, _: u32, _: u32) {}
+    }
+
+    fn key(keycode: Keycode) -> Event {
+        Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: Mod::NOMOD,
+            repeat: false,
+        }
+    }
+
+    fn widget(handles: Keycode, closes_on: Keycode) -> (Box<KeyWidget>, Rc<RefCell<Vec<Keycode>>>) {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let w = KeyWidget {
+            handles,
+            closes_on,
+            seen: seen.clone(),
+        };
+        (Box::new(w), seen)
+    }
+
+    #[test]
+    fn test_events_fall_through() {
+        let mut stack = WidgetStack::new();
+        let (bottom, bottom_seen) = widget(Keycode::A, Keycode::Q);
+        let (top, top_seen) = widget(Keycode::B, Keycode::Q);
+        stack.push(bottom, 0, 0);
+        stack.push(top, 0, 0);
+
+        assert!(stack.handle_event(&key(Keycode::B)));
+        assert!(stack.handle_event(&key(Keycode::A)));
+        assert!(!stack.handle_event(&key(Keycode::C)));
+        assert_eq!(*top_seen.borrow(), vec![Keycode::B, Keycode::A, Keycode::C]);
+        assert_eq!(*bottom_seen.borrow(), vec![Keycode::A, Keycode::C]);
+    }
+
+    #[test]
+    fn test_back_out_and_close() {
+        let mut stack = WidgetStack::new();
+        let (bottom, _) = widget(Keycode::A, Keycode::Q);
+        let (top, _) = widget(Keycode::B, Keycode::Q);
+        let bottom_id = stack.push(bottom, 0, 0);
+        let top_id = stack.push(top, 0, 0);
+
+        assert!(stack.handle_event(&key(Keycode::Escape)));
+        assert!(!stack.contains(top_id));
+        assert_eq!(stack.top(), Some(bottom_id));
+
+        assert!(stack.handle_event(&key(Keycode::Q)));
+        assert!(stack.is_empty());
+        assert!(!stack.handle_event(&key(Keycode::Escape)));
+    }
+
+    #[test]
+    fn test_remove_by_id() {
+        let mut stack = WidgetStack::new();
+        let first = stack.push(widget(Keycode::A, Keycode::Q).0, 0, 0);
+        let second = stack.push(widget(Keycode::B, Keycode::Q).0, 0, 0);
+        assert!(stack.remove(first).is_some());
+        assert!(stack.remove(first).is_none());
+        assert_eq!(stack.top(), Some(second));
+        assert_eq!(stack.len(), 1);
+        assert!(stack.pop().is_some());
+        assert!(stack.pop().is_none());
+    }
+}